@@ -0,0 +1,55 @@
+//! Throughput of the word-at-a-time `munge_in_place`, on one thread and several, against
+//! bytewise munging.
+//!
+//! Run with `cargo bench --bench munge`.
+
+use exercism::xorcism::Xorcism;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const DATA_LEN: usize = 16 * 1024 * 1024;
+const ROUNDS: u32 = 8;
+const THREADS: usize = 4;
+
+/// Munge the data `ROUNDS` times, returning the best time of a single round
+fn time(data: &mut [u8], mut munge: impl FnMut(&mut [u8])) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            munge(black_box(&mut *data));
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, key_len: usize, elapsed: Duration) {
+    let mib_per_sec = DATA_LEN as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64();
+    println!("{:>10} {:>5} {:>12.1} MiB/s", name, key_len, mib_per_sec);
+}
+
+fn main() {
+    let mut data: Vec<u8> = (0..DATA_LEN).map(|i| i as u8).collect();
+
+    println!("{:>10} {:>5} {:>18}", "path", "key", "throughput");
+    for key_len in [1, 5, 16, 64, 255, 256, 4096] {
+        let key: Vec<u8> = (0..key_len).map(|i| (i * 31 + 17) as u8).collect();
+
+        let mut xs = Xorcism::new(&key);
+        let elapsed = time(&mut data, |data| {
+            // munging zeroes yields the keystream itself
+            for (byte, key_byte) in data.iter_mut().zip(xs.munge(std::iter::repeat(0_u8))) {
+                *byte ^= key_byte;
+            }
+        });
+        report("bytewise", key_len, elapsed);
+
+        let mut xs = Xorcism::new(&key);
+        let elapsed = time(&mut data, |data| xs.munge_in_place(data));
+        report("in place", key_len, elapsed);
+
+        let mut xs = Xorcism::new(&key);
+        let elapsed = time(&mut data, |data| xs.munge_in_place_parallel(data, THREADS));
+        report("parallel", key_len, elapsed);
+    }
+}
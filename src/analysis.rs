@@ -0,0 +1,216 @@
+//! Cryptanalysis of data munged with a repeating key.
+//!
+//! Repeating-key XOR leaks the key period: bytes a whole key length apart were XORed with
+//! the same key byte, so they keep the statistics of the plaintext, while bytes at any other
+//! distance look much closer to random.
+
+pub mod known_plaintext;
+pub mod many_time_pad;
+pub mod scoring;
+
+pub use scoring::{ByteFrequencies, PlaintextScorer};
+
+use crate::xorcism::Xorcism;
+
+/// A multiple of a key length must score better by this factor to be ranked ahead of it
+const SIMILAR_SCORE: f64 = 1.25;
+
+/// How many of the most likely key lengths [`break_repeating_key`] tries
+const CANDIDATE_LENGTHS: usize = 3;
+
+/// A candidate key length, with the evidence for it
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyLength {
+    /// The key length in bytes
+    pub len: usize,
+    /// Mean Hamming distance between neighbouring blocks of `len` bytes, per bit.
+    ///
+    /// Around 0.5 for unrelated data; lower when the blocks were munged with the same key.
+    pub distance: f64,
+    /// Mean index of coincidence of the columns of bytes `len` apart, relative to uniformly
+    /// random bytes.
+    ///
+    /// Around 1.0 for unrelated data; higher when each column was munged with a single key byte.
+    pub coincidence: f64,
+    /// How likely this is to be the key length; higher is more likely
+    pub score: f64,
+}
+
+/// The number of bits which differ between `a` and `b`
+///
+/// Only the bytes the two have in common are compared.
+pub fn hamming_distance(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum()
+}
+
+/// The probability that two bytes drawn from `data` without replacement are equal
+///
+/// Returns 0 when there are fewer than two bytes.
+pub fn index_of_coincidence(data: impl IntoIterator<Item = u8>) -> f64 {
+    let mut counts = [0_u64; 256];
+    let mut total = 0_u64;
+    for byte in data {
+        counts[byte as usize] += 1;
+        total += 1;
+    }
+
+    if total < 2 {
+        return 0.0;
+    }
+
+    let pairs: u64 = counts.iter().map(|&n| n * n.saturating_sub(1)).sum();
+    pairs as f64 / (total * (total - 1)) as f64
+}
+
+/// Mean Hamming distance per bit between neighbouring blocks of `len` bytes
+fn block_distance(ciphertext: &[u8], len: usize) -> f64 {
+    let blocks: Vec<&[u8]> = ciphertext.chunks_exact(len).collect();
+    let distance: u32 = blocks
+        .windows(2)
+        .map(|pair| hamming_distance(pair[0], pair[1]))
+        .sum();
+    let bits = (blocks.len() - 1) * len * 8;
+
+    distance as f64 / bits as f64
+}
+
+/// Mean index of coincidence of the `len` columns of bytes `len` apart, relative to uniform
+fn column_coincidence(ciphertext: &[u8], len: usize) -> f64 {
+    let total: f64 = (0..len)
+        .map(|column| index_of_coincidence(ciphertext.iter().skip(column).step_by(len).copied()))
+        .sum();
+
+    total / len as f64 * 256.0
+}
+
+/// Estimate the length of the key `ciphertext` was munged with, trying lengths up to `max_len`
+///
+/// Candidates are ranked most likely first. Multiples of the true key length share its
+/// statistics, so a length is ranked ahead of its multiples unless they score clearly better.
+/// Lengths which leave fewer than two full blocks of ciphertext can't be judged, and are
+/// left out.
+pub fn estimate_key_lengths(ciphertext: &[u8], max_len: usize) -> Vec<KeyLength> {
+    let max_len = max_len.min(ciphertext.len() / 2);
+    let mut candidates: Vec<KeyLength> = (1..=max_len)
+        .map(|len| {
+            let distance = block_distance(ciphertext, len);
+            let coincidence = column_coincidence(ciphertext, len);
+            KeyLength {
+                len,
+                distance,
+                coincidence,
+                score: coincidence * (1.0 - distance),
+            }
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let mut ranked: Vec<KeyLength> = Vec::with_capacity(candidates.len());
+    for candidate in candidates {
+        let at = ranked
+            .iter()
+            .position(|other| {
+                other.len % candidate.len == 0 && other.score < candidate.score * SIMILAR_SCORE
+            })
+            .unwrap_or(ranked.len());
+        ranked.insert(at, candidate);
+    }
+
+    ranked
+}
+
+/// A key recovered from ciphertext
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredKey {
+    /// The most plausible key
+    pub key: Vec<u8>,
+    /// How sure we are that every byte of the key is right, from 0 to 1
+    pub confidence: f64,
+    /// The ciphertext munged with the recovered key
+    pub plaintext: Vec<u8>,
+}
+
+/// Recover a key of `key_len` bytes from `ciphertext`, judging candidate plaintexts by `scorer`
+///
+/// Each key byte is only ever XORed with one column of bytes `key_len` apart, so each is
+/// recovered on its own by trying all 256 values and keeping the one whose column looks most
+/// like plaintext. A key byte's confidence is how much more likely the best value is than the
+/// runner-up, taking the gap between their scores as log odds; the key's confidence is that of
+/// all its bytes together. That makes it a true probability only for log-likelihood scorers
+/// such as [`ByteFrequencies`].
+///
+/// Columns hold every `key_len`th byte, so scorers which look at how neighbouring bytes fit
+/// together, like [`scoring::Utf8`], only make sense here for single-byte keys.
+///
+/// Returns `None` if `key_len` is 0 or longer than the ciphertext.
+pub fn recover_key<Scorer>(
+    ciphertext: &[u8],
+    key_len: usize,
+    scorer: &Scorer,
+) -> Option<RecoveredKey>
+where
+    Scorer: PlaintextScorer + ?Sized,
+{
+    if key_len == 0 || key_len > ciphertext.len() {
+        return None;
+    }
+
+    let mut key = Vec::with_capacity(key_len);
+    let mut confidence = 1.0;
+    let mut candidate = Vec::new();
+    for column in 0..key_len {
+        let column: Vec<u8> = ciphertext
+            .iter()
+            .skip(column)
+            .step_by(key_len)
+            .copied()
+            .collect();
+        let mut scores: Vec<(u8, f64)> = (0..=u8::MAX)
+            .map(|key_byte| {
+                candidate.clear();
+                candidate.extend(column.iter().map(|byte| byte ^ key_byte));
+                (key_byte, scorer.score(&candidate))
+            })
+            .collect();
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let (best, best_score) = scores[0];
+        let runner_up_score = scores[1].1;
+        key.push(best);
+        // the odds of the best over the runner-up are the exponent of the score gap
+        confidence *= 1.0 / (1.0 + (runner_up_score - best_score).exp());
+    }
+
+    let plaintext = Xorcism::new(&key).munge(ciphertext).collect();
+    Some(RecoveredKey {
+        key,
+        confidence,
+        plaintext,
+    })
+}
+
+/// Recover the key from `ciphertext` without knowing its length
+///
+/// The most likely key lengths up to `max_len` are each tried with [`recover_key`], and the
+/// most confident key is returned. Multiples of the true length recover the same key repeated,
+/// but from shorter columns and with more bytes to get right, so they are less confident.
+pub fn break_repeating_key<Scorer>(
+    ciphertext: &[u8],
+    max_len: usize,
+    scorer: &Scorer,
+) -> Option<RecoveredKey>
+where
+    Scorer: PlaintextScorer + ?Sized,
+{
+    estimate_key_lengths(ciphertext, max_len)
+        .iter()
+        .take(CANDIDATE_LENGTHS)
+        .filter_map(|candidate| recover_key(ciphertext, candidate.len, scorer))
+        .reduce(|best, recovered| {
+            if recovered.confidence > best.confidence {
+                recovered
+            } else {
+                best
+            }
+        })
+}
@@ -0,0 +1,270 @@
+//! Key extraction from known plaintext.
+//!
+//! Wherever the plaintext is known, the keystream is just `ciphertext ^ plaintext`. File
+//! headers, magic numbers and fixed prefixes like `{"version":` give away key bytes directly,
+//! and a long enough crib gives away the key period too.
+
+use std::fmt;
+
+/// A stretch of plaintext known to sit at `offset` in the ciphertext
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crib<'a> {
+    /// Where the known plaintext starts, in bytes from the start of the ciphertext
+    pub offset: usize,
+    /// The known plaintext
+    pub plaintext: &'a [u8],
+}
+
+impl<'a> Crib<'a> {
+    /// Known `plaintext` at `offset`
+    pub fn new<Plaintext>(offset: usize, plaintext: &'a Plaintext) -> Crib<'a>
+    where
+        Plaintext: AsRef<[u8]> + ?Sized,
+    {
+        Crib {
+            offset,
+            plaintext: plaintext.as_ref(),
+        }
+    }
+}
+
+/// Reasons the cribs could not produce a key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KnownPlaintextError {
+    /// A crib runs past the end of the ciphertext
+    CribOutOfBounds {
+        /// Where the crib starts
+        offset: usize,
+        /// How long the crib is
+        len: usize,
+    },
+    /// Two cribs imply different values for the same key byte
+    Contradiction {
+        /// The key byte they disagree on
+        key_index: usize,
+        /// The ciphertext offsets of the disagreeing bytes
+        offsets: (usize, usize),
+        /// The key byte values implied at those offsets
+        values: (u8, u8),
+    },
+    /// The key length was 0
+    EmptyKey,
+    /// No key length up to the maximum tried agrees with every crib
+    NoConsistentLength,
+    /// The cribs agree with some key lengths, but never overlap under any of them, so they
+    /// can't tell which is right
+    LengthUndetermined,
+}
+
+impl fmt::Display for KnownPlaintextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KnownPlaintextError::CribOutOfBounds { offset, len } => write!(
+                f,
+                "crib of {} bytes at offset {} runs past the end of the ciphertext",
+                len, offset
+            ),
+            KnownPlaintextError::Contradiction {
+                key_index,
+                offsets,
+                values,
+            } => write!(
+                f,
+                "cribs disagree on key byte {}: {:#04x} at offset {} but {:#04x} at offset {}",
+                key_index, values.0, offsets.0, values.1, offsets.1
+            ),
+            KnownPlaintextError::EmptyKey => write!(f, "key length must not be 0"),
+            KnownPlaintextError::NoConsistentLength => {
+                write!(f, "no key length agrees with every crib")
+            }
+            KnownPlaintextError::LengthUndetermined => {
+                write!(f, "cribs are too short to determine the key length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KnownPlaintextError {}
+
+/// A key of known length with possibly some bytes still unknown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialKey {
+    bytes: Vec<Option<u8>>,
+}
+
+impl PartialKey {
+    /// The length of the key
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Whether the key has no bytes at all
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The key byte at `index`, if it is known
+    pub fn get(&self, index: usize) -> Option<u8> {
+        self.bytes.get(index).copied().flatten()
+    }
+
+    /// Every key byte, with `None` for those which are unknown
+    pub fn bytes(&self) -> &[Option<u8>] {
+        &self.bytes
+    }
+
+    /// The indices of the key bytes which are still unknown
+    pub fn unknown(&self) -> Vec<usize> {
+        (0..self.len())
+            .filter(|&index| self.bytes[index].is_none())
+            .collect()
+    }
+
+    /// Whether every key byte is known
+    pub fn is_complete(&self) -> bool {
+        self.bytes.iter().all(Option::is_some)
+    }
+
+    /// The key, if every byte of it is known
+    pub fn to_key(&self) -> Option<Vec<u8>> {
+        self.bytes.iter().copied().collect()
+    }
+
+    /// The key with every unknown byte replaced by `filler`
+    ///
+    /// Munging with this key recovers the plaintext wherever the key is known.
+    pub fn fill(&self, filler: u8) -> Vec<u8> {
+        self.bytes
+            .iter()
+            .map(|byte| byte.unwrap_or(filler))
+            .collect()
+    }
+}
+
+/// Hex, with `??` for each unknown byte
+impl fmt::Display for PartialKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.bytes {
+            match byte {
+                Some(byte) => write!(f, "{:02x}", byte)?,
+                None => write!(f, "??")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The keystream bytes given away by the cribs, as `(ciphertext offset, keystream byte)`
+fn keystream(ciphertext: &[u8], cribs: &[Crib]) -> Result<Vec<(usize, u8)>, KnownPlaintextError> {
+    let mut known = Vec::new();
+    for crib in cribs {
+        let cipher = crib
+            .offset
+            .checked_add(crib.plaintext.len())
+            .and_then(|end| ciphertext.get(crib.offset..end))
+            .ok_or(KnownPlaintextError::CribOutOfBounds {
+                offset: crib.offset,
+                len: crib.plaintext.len(),
+            })?;
+        known.extend(
+            cipher
+                .iter()
+                .zip(crib.plaintext)
+                .enumerate()
+                .map(|(i, (cipher, plain))| (crib.offset + i, cipher ^ plain)),
+        );
+    }
+
+    Ok(known)
+}
+
+/// Fold known keystream bytes into a key of `key_len` bytes, stopping at the first
+/// contradiction. Also returns how many key bytes were seen more than once, i.e. how many
+/// times the key length was put to the test.
+fn fold_keystream(
+    known: &[(usize, u8)],
+    key_len: usize,
+) -> Result<(PartialKey, usize), KnownPlaintextError> {
+    let mut bytes = vec![None; key_len];
+    let mut first_seen = vec![0; key_len];
+    let mut overlaps = 0;
+    for &(offset, value) in known {
+        let key_index = offset % key_len;
+        match bytes[key_index] {
+            None => {
+                bytes[key_index] = Some(value);
+                first_seen[key_index] = offset;
+            }
+            Some(existing) if existing == value => {
+                // cribs overlapping in the ciphertext itself don't test the key length
+                if first_seen[key_index] != offset {
+                    overlaps += 1;
+                }
+            }
+            Some(existing) => {
+                return Err(KnownPlaintextError::Contradiction {
+                    key_index,
+                    offsets: (first_seen[key_index], offset),
+                    values: (existing, value),
+                })
+            }
+        }
+    }
+
+    Ok((PartialKey { bytes }, overlaps))
+}
+
+/// Extract as much of a key of `key_len` bytes as the cribs give away
+///
+/// Offsets are counted from the start of the keystream, so the ciphertext should start at
+/// keystream position 0. Key bytes no crib covers are left unknown.
+pub fn key_from_cribs(
+    ciphertext: &[u8],
+    key_len: usize,
+    cribs: &[Crib],
+) -> Result<PartialKey, KnownPlaintextError> {
+    if key_len == 0 {
+        return Err(KnownPlaintextError::EmptyKey);
+    }
+
+    let known = keystream(ciphertext, cribs)?;
+    fold_keystream(&known, key_len).map(|(key, _)| key)
+}
+
+/// Find the shortest key length up to `max_len` which the cribs both agree with and put to
+/// the test
+///
+/// A length is only put to the test when some key byte is covered by the cribs more than
+/// once, so a single crib must be longer than the key to give its length away.
+pub fn infer_key_length(
+    ciphertext: &[u8],
+    cribs: &[Crib],
+    max_len: usize,
+) -> Result<usize, KnownPlaintextError> {
+    let known = keystream(ciphertext, cribs)?;
+    let mut consistent = false;
+    for key_len in 1..=max_len {
+        match fold_keystream(&known, key_len) {
+            Ok((_, overlaps)) if overlaps > 0 => return Ok(key_len),
+            Ok(_) => consistent = true,
+            Err(_) => {}
+        }
+    }
+
+    if consistent {
+        Err(KnownPlaintextError::LengthUndetermined)
+    } else {
+        Err(KnownPlaintextError::NoConsistentLength)
+    }
+}
+
+/// Infer the key length from the cribs with [`infer_key_length`], then extract as much of
+/// the key as they give away
+pub fn infer_key(
+    ciphertext: &[u8],
+    cribs: &[Crib],
+    max_len: usize,
+) -> Result<PartialKey, KnownPlaintextError> {
+    let key_len = infer_key_length(ciphertext, cribs, max_len)?;
+    key_from_cribs(ciphertext, key_len, cribs)
+}
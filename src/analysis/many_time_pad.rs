@@ -0,0 +1,177 @@
+//! Crib dragging across messages munged with the same keystream.
+//!
+//! When the same key munges several messages from the same starting position, XORing two of
+//! the ciphertexts cancels the keystream and leaves the XOR of the two plaintexts. Sliding a
+//! guessed word (a crib) along that XOR reveals the other message's text wherever the guess is
+//! right, and every confirmed guess gives away keystream which decrypts all the messages there.
+
+use super::PlaintextScorer;
+use std::fmt;
+
+/// A place where dragging a crib across two ciphertexts revealed plausible text
+///
+/// The crib could be in either message: if it is in one, `revealed` is in the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CribMatch {
+    /// The indices of the two ciphertexts, lowest first
+    pub pair: (usize, usize),
+    /// Where the crib was placed, in bytes from the start of the messages
+    pub offset: usize,
+    /// The other message's text at `offset`, if the crib is right
+    pub revealed: Vec<u8>,
+    /// How plausible `revealed` is as plaintext; higher is more plausible
+    pub score: f64,
+}
+
+/// Reasons plaintext could not be placed in a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaceError {
+    /// There is no message with that index
+    NoSuchMessage(usize),
+    /// The plaintext runs past the end of the message
+    OutOfBounds {
+        /// Where the plaintext starts
+        offset: usize,
+        /// How long the plaintext is
+        len: usize,
+    },
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceError::NoSuchMessage(message) => write!(f, "no message {}", message),
+            PlaceError::OutOfBounds { offset, len } => write!(
+                f,
+                "{} bytes at offset {} run past the end of the message",
+                len, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlaceError {}
+
+/// Whether `byte` could appear in ordinary text
+fn is_text(byte: u8) -> bool {
+    byte.is_ascii_graphic() || matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// The XOR of two ciphertexts over the length they have in common
+///
+/// If they were munged with the same keystream, this is the XOR of their plaintexts.
+pub fn xor_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b).map(|(a, b)| a ^ b).collect()
+}
+
+/// Several ciphertexts munged with the same keystream, and what is known of that keystream
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManyTimePad {
+    ciphertexts: Vec<Vec<u8>>,
+    keystream: Vec<Option<u8>>,
+}
+
+impl ManyTimePad {
+    /// Start cracking `ciphertexts`, all munged from the same keystream position
+    pub fn new(ciphertexts: Vec<Vec<u8>>) -> ManyTimePad {
+        let len = ciphertexts.iter().map(Vec::len).max().unwrap_or(0);
+        ManyTimePad {
+            ciphertexts,
+            keystream: vec![None; len],
+        }
+    }
+
+    /// The ciphertexts being cracked
+    pub fn ciphertexts(&self) -> &[Vec<u8>] {
+        &self.ciphertexts
+    }
+
+    /// The keystream as far as it is known
+    pub fn keystream(&self) -> &[Option<u8>] {
+        &self.keystream
+    }
+
+    /// Slide `crib` along the XOR of every pair of ciphertexts, and report every offset where
+    /// it reveals nothing but text, most plausible to `scorer` first
+    pub fn drag<Scorer>(&self, crib: &[u8], scorer: &Scorer) -> Vec<CribMatch>
+    where
+        Scorer: PlaintextScorer + ?Sized,
+    {
+        let mut matches = Vec::new();
+        if crib.is_empty() {
+            return matches;
+        }
+
+        for a in 0..self.ciphertexts.len() {
+            for b in a + 1..self.ciphertexts.len() {
+                let xored = xor_pair(&self.ciphertexts[a], &self.ciphertexts[b]);
+                for (offset, window) in xored.windows(crib.len()).enumerate() {
+                    let revealed: Vec<u8> = window.iter().zip(crib).map(|(x, c)| x ^ c).collect();
+                    if !revealed.iter().copied().all(is_text) {
+                        continue;
+                    }
+
+                    let score = scorer.score(&revealed);
+                    matches.push(CribMatch {
+                        pair: (a, b),
+                        offset,
+                        revealed,
+                        score,
+                    });
+                }
+            }
+        }
+
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+
+    /// Declare that `message` holds `plaintext` at `offset`, filling in the keystream there
+    ///
+    /// Anything previously known of the keystream at those positions is overwritten.
+    pub fn place(
+        &mut self,
+        message: usize,
+        offset: usize,
+        plaintext: &[u8],
+    ) -> Result<(), PlaceError> {
+        let ciphertext = self
+            .ciphertexts
+            .get(message)
+            .ok_or(PlaceError::NoSuchMessage(message))?;
+        let cipher = offset
+            .checked_add(plaintext.len())
+            .and_then(|end| ciphertext.get(offset..end))
+            .ok_or(PlaceError::OutOfBounds {
+                offset,
+                len: plaintext.len(),
+            })?;
+
+        for (i, (cipher, plain)) in cipher.iter().zip(plaintext).enumerate() {
+            self.keystream[offset + i] = Some(cipher ^ plain);
+        }
+
+        Ok(())
+    }
+
+    /// Forget the keystream from `offset` for `len` bytes
+    pub fn forget(&mut self, offset: usize, len: usize) {
+        let end = offset.saturating_add(len).min(self.keystream.len());
+        if let Some(keystream) = self.keystream.get_mut(offset..end) {
+            keystream.fill(None);
+        }
+    }
+
+    /// The plaintext of `message` as far as the keystream is known, or `None` if there is no
+    /// such message
+    pub fn plaintext(&self, message: usize) -> Option<Vec<Option<u8>>> {
+        let ciphertext = self.ciphertexts.get(message)?;
+        Some(
+            ciphertext
+                .iter()
+                .zip(&self.keystream)
+                .map(|(cipher, key)| key.map(|key| cipher ^ key))
+                .collect(),
+        )
+    }
+}
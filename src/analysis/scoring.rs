@@ -0,0 +1,272 @@
+//! Judging whether bytes look like plaintext.
+//!
+//! Every attack on munged data ends up asking which of several candidate decryptions looks
+//! most like the real thing. What "looks like plaintext" means depends on the data, so the
+//! analysis functions take any [`PlaintextScorer`].
+
+use std::{
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+/// Something which can judge how much bytes look like plaintext
+pub trait PlaintextScorer {
+    /// How much `data` looks like plaintext; higher is more plausible
+    ///
+    /// Scores are only compared between candidates of the same length. Log-likelihood
+    /// scorers, whose score gaps are log odds, give the most meaningful confidences.
+    fn score(&self, data: &[u8]) -> f64;
+}
+
+impl<Scorer> PlaintextScorer for &Scorer
+where
+    Scorer: PlaintextScorer + ?Sized,
+{
+    fn score(&self, data: &[u8]) -> f64 {
+        (**self).score(data)
+    }
+}
+
+impl<Scorer> PlaintextScorer for Box<Scorer>
+where
+    Scorer: PlaintextScorer + ?Sized,
+{
+    fn score(&self, data: &[u8]) -> f64 {
+        (**self).score(data)
+    }
+}
+
+/// Relative frequencies of letters in English text, in percent
+const ENGLISH_LETTERS: [(u8, f64); 26] = [
+    (b'a', 8.17),
+    (b'b', 1.29),
+    (b'c', 2.78),
+    (b'd', 4.25),
+    (b'e', 12.70),
+    (b'f', 2.23),
+    (b'g', 2.02),
+    (b'h', 6.09),
+    (b'i', 6.97),
+    (b'j', 0.15),
+    (b'k', 0.77),
+    (b'l', 4.03),
+    (b'm', 2.41),
+    (b'n', 6.75),
+    (b'o', 7.51),
+    (b'p', 1.93),
+    (b'q', 0.10),
+    (b'r', 5.99),
+    (b's', 6.33),
+    (b't', 9.06),
+    (b'u', 2.76),
+    (b'v', 0.98),
+    (b'w', 2.36),
+    (b'x', 0.15),
+    (b'y', 1.97),
+    (b'z', 0.07),
+];
+
+/// A model of plaintext as the probability of seeing each byte value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ByteFrequencies {
+    probabilities: [f64; 256],
+}
+
+impl ByteFrequencies {
+    /// Build a model from relative weights for each byte value
+    ///
+    /// The weights needn't sum to anything in particular, but every byte value should have
+    /// some weight: a byte the model considers impossible rules out any key which produces it.
+    pub fn from_weights(weights: [f64; 256]) -> ByteFrequencies {
+        let total: f64 = weights.iter().sum();
+        ByteFrequencies {
+            probabilities: weights.map(|weight| weight / total),
+        }
+    }
+
+    /// A model of English prose in ASCII: mostly lowercase letters and spaces, some capitals,
+    /// punctuation and line breaks, and very little else
+    pub fn english() -> ByteFrequencies {
+        let mut weights = [0.001; 256];
+        for byte in b' '..=b'~' {
+            weights[byte as usize] = 0.02;
+        }
+        for (letter, percent) in ENGLISH_LETTERS {
+            weights[letter as usize] = percent * 0.97;
+            weights[letter.to_ascii_uppercase() as usize] = percent * 0.03;
+        }
+        for (byte, weight) in [
+            (b' ', 20.0),
+            (b'\n', 1.5),
+            (b'.', 1.0),
+            (b',', 1.2),
+            (b'\'', 0.3),
+            (b'"', 0.3),
+            (b'-', 0.2),
+        ] {
+            weights[byte as usize] = weight;
+        }
+        for digit in b'0'..=b'9' {
+            weights[digit as usize] = 0.05;
+        }
+
+        Self::from_weights(weights)
+    }
+
+    /// A model learned from a sample of typical plaintext
+    ///
+    /// Every byte value is counted once more than it appears, so that bytes missing from the
+    /// sample are unlikely rather than impossible.
+    pub fn from_sample(sample: &[u8]) -> ByteFrequencies {
+        let mut weights = [1.0; 256];
+        for &byte in sample {
+            weights[byte as usize] += 1.0;
+        }
+
+        Self::from_weights(weights)
+    }
+
+    /// A model learned from a corpus of typical plaintext, such as a log file, a dump of
+    /// JSON documents or prose in the expected language
+    pub fn from_corpus(corpus: impl Read) -> io::Result<ByteFrequencies> {
+        let mut weights = [1.0; 256];
+        for byte in BufReader::new(corpus).bytes() {
+            weights[byte? as usize] += 1.0;
+        }
+
+        Ok(Self::from_weights(weights))
+    }
+
+    /// A model learned from the corpus in the file at `path`
+    pub fn from_corpus_file(path: impl AsRef<Path>) -> io::Result<ByteFrequencies> {
+        Self::from_corpus(File::open(path)?)
+    }
+
+    /// The probability of `byte` under this model
+    pub fn probability(&self, byte: u8) -> f64 {
+        self.probabilities[byte as usize]
+    }
+
+    /// The log-likelihood of `data` under this model; higher means more plausible
+    pub fn log_likelihood(&self, data: impl IntoIterator<Item = u8>) -> f64 {
+        data.into_iter()
+            .map(|byte| self.probability(byte).ln())
+            .sum()
+    }
+}
+
+/// The log-likelihood of the data under the model
+impl PlaintextScorer for ByteFrequencies {
+    fn score(&self, data: &[u8]) -> f64 {
+        self.log_likelihood(data.iter().copied())
+    }
+}
+
+/// Compares the histogram of the data with the byte frequencies expected of plaintext
+///
+/// The score is minus half the chi-squared statistic, which approximates a log-likelihood.
+/// Unlike [`ByteFrequencies`] on its own, this notices when the data has the right kinds of
+/// bytes in the wrong proportions, such as nothing but `e`s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChiSquared {
+    expected: ByteFrequencies,
+}
+
+impl ChiSquared {
+    /// Compare data against the frequencies of `expected`
+    pub fn new(expected: ByteFrequencies) -> ChiSquared {
+        ChiSquared { expected }
+    }
+
+    /// The chi-squared statistic of `data` against the expected frequencies; lower is closer
+    ///
+    /// Byte values the model gives no weight are left out, rather than counting as infinitely
+    /// far off.
+    pub fn statistic(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return 0.0;
+        }
+
+        let mut counts = [0_usize; 256];
+        for &byte in data {
+            counts[byte as usize] += 1;
+        }
+
+        (0..=u8::MAX)
+            .map(|byte| {
+                let expected = self.expected.probability(byte) * data.len() as f64;
+                (counts[byte as usize] as f64, expected)
+            })
+            // also skips the NaNs from a model with no weight at all
+            .filter(|&(_, expected)| expected > 0.0)
+            .map(|(count, expected)| {
+                let difference = count - expected;
+                difference * difference / expected
+            })
+            .sum()
+    }
+}
+
+impl PlaintextScorer for ChiSquared {
+    fn score(&self, data: &[u8]) -> f64 {
+        -self.statistic(data) / 2.0
+    }
+}
+
+/// The fraction of the data which is printable ASCII or whitespace
+///
+/// Knows nothing about language, so suits logs, source code and other text whose letter
+/// frequencies are unusual. Many candidates can tie at 1.0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrintableAscii;
+
+impl PlaintextScorer for PrintableAscii {
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return 1.0;
+        }
+
+        let printable = data
+            .iter()
+            .filter(|&&byte| {
+                byte.is_ascii_graphic() || matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+            })
+            .count();
+        printable as f64 / data.len() as f64
+    }
+}
+
+/// The fraction of the data which is valid UTF-8
+///
+/// Suits text in any language, but judges how neighbouring bytes fit together, so it needs
+/// contiguous candidates rather than columns of every `n`th byte.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Utf8;
+
+impl PlaintextScorer for Utf8 {
+    fn score(&self, data: &[u8]) -> f64 {
+        if data.is_empty() {
+            return 1.0;
+        }
+
+        let mut valid = 0;
+        let mut rest = data;
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(_) => {
+                    valid += rest.len();
+                    break;
+                }
+                Err(err) => {
+                    valid += err.valid_up_to();
+                    // an incomplete sequence at the very end is invalid too
+                    let invalid = err.error_len().unwrap_or(rest.len() - err.valid_up_to());
+                    rest = &rest[err.valid_up_to() + invalid..];
+                }
+            }
+        }
+
+        valid as f64 / data.len() as f64
+    }
+}
@@ -0,0 +1,523 @@
+//! Text armor for munged data, so it can be pasted into tickets and config files.
+//!
+//! Munged data is arbitrary bytes; these adapters spell it out as hex, Base64 (RFC 4648, with
+//! the standard or the URL-safe alphabet) or PEM-style armor: Base64 wrapped at
+//! [`LINE_LEN`] columns between `BEGIN` and `END` markers. They compose with the munging
+//! adapters in either order, for example to armor munged output:
+//!
+//! ```
+//! use exercism::armor::{EncodeWriter, Encoding};
+//! use exercism::xorcism::Xorcism;
+//! use std::io::Write;
+//!
+//! let mut writer = Xorcism::new("abcde").writer(EncodeWriter::new(Encoding::Hex, Vec::new()));
+//! writer.write_all(b"hi").unwrap();
+//! let (encoder, _) = writer.into_inner();
+//! assert_eq!(encoder.finish().unwrap(), b"090b");
+//! ```
+//!
+//! Decoding skips whitespace, so wrapped or indented text reads back, and PEM armor may have
+//! other text before and after it.
+
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    str::FromStr,
+};
+
+/// The label in the PEM markers
+pub const PEM_LABEL: &str = "XORCISM DATA";
+
+/// The number of Base64 characters on each line of PEM armor
+pub const LINE_LEN: usize = 64;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// A way of spelling out bytes as text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Two lowercase hex digits per byte; either case reads back
+    Hex,
+    /// Base64 with the standard alphabet, padded with `=`
+    Base64,
+    /// Base64 with the URL and filename safe alphabet, unpadded; padding reads back
+    Base64Url,
+    /// Base64 wrapped at [`LINE_LEN`] columns between `BEGIN` and `END` markers
+    Pem,
+}
+
+impl Encoding {
+    /// The name of the encoding, as parsed by [`str::parse`]
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+            Encoding::Base64Url => "base64url",
+            Encoding::Pem => "pem",
+        }
+    }
+
+    fn alphabet(self) -> &'static [u8; 64] {
+        match self {
+            Encoding::Base64Url => BASE64_URL,
+            _ => BASE64,
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The name given for an [`Encoding`] isn't one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseEncodingError(String);
+
+impl fmt::Display for ParseEncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown encoding {:?}; expected hex, base64, base64url or pem",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for ParseEncodingError {}
+
+impl FromStr for Encoding {
+    type Err = ParseEncodingError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "hex" => Ok(Encoding::Hex),
+            "base64" => Ok(Encoding::Base64),
+            "base64url" => Ok(Encoding::Base64Url),
+            "pem" => Ok(Encoding::Pem),
+            _ => Err(ParseEncodingError(name.to_string())),
+        }
+    }
+}
+
+/// Reasons text fails to decode
+///
+/// Wrapped in an [`io::Error`] of kind [`io::ErrorKind::InvalidData`] by [`DecodeReader`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ArmorError {
+    /// A character which isn't part of the encoding
+    InvalidCharacter(u8),
+    /// The text ends partway through a byte
+    Truncated,
+    /// `=` padding in the wrong place, or the wrong amount of it
+    InvalidPadding,
+    /// PEM armor has no `BEGIN` marker
+    MissingBegin,
+    /// PEM armor has no `END` marker
+    MissingEnd,
+}
+
+impl fmt::Display for ArmorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArmorError::InvalidCharacter(byte) => {
+                write!(f, "invalid character {:?} in encoded data", *byte as char)
+            }
+            ArmorError::Truncated => write!(f, "encoded data is truncated"),
+            ArmorError::InvalidPadding => write!(f, "invalid padding in encoded data"),
+            ArmorError::MissingBegin => write!(f, "no BEGIN {} marker", PEM_LABEL),
+            ArmorError::MissingEnd => write!(f, "no END {} marker", PEM_LABEL),
+        }
+    }
+}
+
+impl std::error::Error for ArmorError {}
+
+impl From<ArmorError> for io::Error {
+    fn from(err: ArmorError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, err)
+    }
+}
+
+fn begin_marker() -> String {
+    format!("-----BEGIN {}-----", PEM_LABEL)
+}
+
+fn end_marker() -> String {
+    format!("-----END {}-----", PEM_LABEL)
+}
+
+/// Encodes bytes a piece at a time, carrying partial Base64 groups between pieces
+struct Encoder {
+    encoding: Encoding,
+    group: [u8; 3],
+    group_len: usize,
+    column: usize,
+    begun: bool,
+}
+
+impl Encoder {
+    fn new(encoding: Encoding) -> Encoder {
+        Encoder {
+            encoding,
+            group: [0; 3],
+            group_len: 0,
+            column: 0,
+            begun: false,
+        }
+    }
+
+    /// Write the `BEGIN` marker of PEM armor, once
+    fn begin(&mut self, out: &mut Vec<u8>) {
+        if self.encoding == Encoding::Pem && !self.begun {
+            out.extend_from_slice(begin_marker().as_bytes());
+            out.push(b'\n');
+        }
+        self.begun = true;
+    }
+
+    /// Write one Base64 character, wrapping lines of PEM armor
+    fn put(&mut self, c: u8, out: &mut Vec<u8>) {
+        if self.encoding == Encoding::Pem && self.column == LINE_LEN {
+            out.push(b'\n');
+            self.column = 0;
+        }
+        out.push(c);
+        self.column += 1;
+    }
+
+    /// Write the Base64 characters for the pending group, which holds `group_len` bytes
+    fn flush_group(&mut self, out: &mut Vec<u8>) {
+        let alphabet = self.encoding.alphabet();
+        let [a, b, c] = self.group;
+        let bits = (a as u32) << 16 | (b as u32) << 8 | c as u32;
+        for i in 0..=self.group_len {
+            self.put(alphabet[(bits >> (18 - 6 * i) & 0x3f) as usize], out);
+        }
+        if self.encoding != Encoding::Base64Url {
+            for _ in self.group_len..3 {
+                self.put(b'=', out);
+            }
+        }
+        self.group = [0; 3];
+        self.group_len = 0;
+    }
+
+    fn encode(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        self.begin(out);
+        if self.encoding == Encoding::Hex {
+            for &byte in data {
+                out.extend_from_slice(&[HEX[(byte >> 4) as usize], HEX[(byte & 0xf) as usize]]);
+            }
+            return;
+        }
+
+        for &byte in data {
+            self.group[self.group_len] = byte;
+            self.group_len += 1;
+            if self.group_len == 3 {
+                self.flush_group(out);
+            }
+        }
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) {
+        self.begin(out);
+        if self.group_len > 0 {
+            self.flush_group(out);
+        }
+        if self.encoding == Encoding::Pem {
+            if self.column > 0 {
+                out.push(b'\n');
+            }
+            out.extend_from_slice(end_marker().as_bytes());
+            out.push(b'\n');
+        }
+    }
+}
+
+/// Where a [`Decoder`] is in PEM armor
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Before,
+    Body,
+    After,
+}
+
+/// Decodes text a piece at a time, carrying partial bytes between pieces
+struct Decoder {
+    encoding: Encoding,
+    bits: u32,
+    bit_len: u32,
+    /// Characters in the current Base64 group
+    group_len: usize,
+    padding: usize,
+    section: Section,
+    /// The current line of PEM armor
+    line: Vec<u8>,
+}
+
+impl Decoder {
+    fn new(encoding: Encoding) -> Decoder {
+        Decoder {
+            encoding,
+            bits: 0,
+            bit_len: 0,
+            group_len: 0,
+            padding: 0,
+            section: Section::Before,
+            line: Vec::new(),
+        }
+    }
+
+    /// Take in one character of hex or Base64
+    fn push(&mut self, c: u8, out: &mut Vec<u8>) -> Result<(), ArmorError> {
+        if c.is_ascii_whitespace() {
+            return Ok(());
+        }
+
+        if self.encoding == Encoding::Hex {
+            let digit = (c as char)
+                .to_digit(16)
+                .ok_or(ArmorError::InvalidCharacter(c))?;
+            self.bits = self.bits << 4 | digit;
+            self.bit_len += 4;
+        } else if c == b'=' {
+            self.padding += 1;
+            if self.group_len < 2 || self.group_len + self.padding > 4 {
+                return Err(ArmorError::InvalidPadding);
+            }
+            return Ok(());
+        } else {
+            if self.padding > 0 {
+                return Err(ArmorError::InvalidPadding);
+            }
+            let value = self
+                .encoding
+                .alphabet()
+                .iter()
+                .position(|&a| a == c)
+                .ok_or(ArmorError::InvalidCharacter(c))?;
+            self.bits = self.bits << 6 | value as u32;
+            self.bit_len += 6;
+            self.group_len = (self.group_len + 1) % 4;
+        }
+
+        if self.bit_len >= 8 {
+            self.bit_len -= 8;
+            out.push((self.bits >> self.bit_len) as u8);
+            self.bits &= (1 << self.bit_len) - 1;
+        }
+        Ok(())
+    }
+
+    /// Check that the hex or Base64 ended on a whole byte
+    fn end(&self) -> Result<(), ArmorError> {
+        match self.encoding {
+            Encoding::Hex if self.bit_len > 0 => Err(ArmorError::Truncated),
+            Encoding::Hex => Ok(()),
+            _ if self.padding > 0 && self.group_len + self.padding != 4 => {
+                Err(ArmorError::InvalidPadding)
+            }
+            _ if self.group_len == 1 => Err(ArmorError::Truncated),
+            _ => Ok(()),
+        }
+    }
+
+    /// Take in a whole line of PEM armor
+    fn pem_line(&mut self, out: &mut Vec<u8>) -> Result<(), ArmorError> {
+        let mut line = std::mem::take(&mut self.line);
+        let text = line.trim_ascii();
+        match self.section {
+            Section::Before if text == begin_marker().as_bytes() => self.section = Section::Body,
+            Section::Body if text == end_marker().as_bytes() => {
+                self.end()?;
+                self.section = Section::After;
+            }
+            // a cut-off or mislabelled marker, rather than Base64 gone wrong
+            Section::Body if text.starts_with(b"-----") => return Err(ArmorError::MissingEnd),
+            Section::Body => {
+                for &c in text {
+                    self.push(c, out)?;
+                }
+            }
+            Section::Before | Section::After => {}
+        }
+        line.clear();
+        self.line = line;
+        Ok(())
+    }
+
+    fn decode(&mut self, text: &[u8], out: &mut Vec<u8>) -> Result<(), ArmorError> {
+        if self.encoding != Encoding::Pem {
+            return text.iter().try_for_each(|&c| self.push(c, out));
+        }
+
+        for &c in text {
+            if self.section == Section::After {
+                break;
+            }
+            if c == b'\n' {
+                self.pem_line(out)?;
+            } else {
+                self.line.push(c);
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, out: &mut Vec<u8>) -> Result<(), ArmorError> {
+        if self.encoding != Encoding::Pem {
+            return self.end();
+        }
+
+        self.pem_line(out)?;
+        match self.section {
+            Section::Before => Err(ArmorError::MissingBegin),
+            Section::Body => Err(ArmorError::MissingEnd),
+            Section::After => Ok(()),
+        }
+    }
+}
+
+/// Spell out `data` in `encoding`
+pub fn encode(encoding: Encoding, data: &[u8]) -> String {
+    let mut out = Vec::new();
+    let mut encoder = Encoder::new(encoding);
+    encoder.encode(data, &mut out);
+    encoder.finish(&mut out);
+    String::from_utf8(out).expect("encodings are ASCII")
+}
+
+/// Read back `text` spelled out in `encoding`
+pub fn decode(encoding: Encoding, text: impl AsRef<[u8]>) -> Result<Vec<u8>, ArmorError> {
+    let mut out = Vec::new();
+    let mut decoder = Decoder::new(encoding);
+    decoder.decode(text.as_ref(), &mut out)?;
+    decoder.finish(&mut out)?;
+    Ok(out)
+}
+
+/// A writer which spells out everything written to it
+///
+/// The end of the text is only written by [`EncodeWriter::finish`]. If writing to the
+/// wrapped writer fails, the text written so far is incomplete.
+pub struct EncodeWriter<W> {
+    writer: W,
+    encoder: Encoder,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> EncodeWriter<W> {
+    /// Spell out in `encoding` into `writer`
+    pub fn new(encoding: Encoding, writer: W) -> EncodeWriter<W> {
+        EncodeWriter {
+            writer,
+            encoder: Encoder::new(encoding),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Write the end of the text, returning the wrapped writer
+    ///
+    /// The wrapped writer is flushed.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.buf.clear();
+        self.encoder.finish(&mut self.buf);
+        self.writer.write_all(&self.buf)?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write> Write for EncodeWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.clear();
+        self.encoder.encode(data, &mut self.buf);
+        self.writer.write_all(&self.buf)?;
+
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A reader which reads back text spelled out in an [`Encoding`]
+///
+/// Text which doesn't decode fails with an [`ArmorError`], on this and every later read.
+pub struct DecodeReader<R> {
+    reader: R,
+    decoder: Decoder,
+    buf: Vec<u8>,
+    pos: usize,
+    done: bool,
+    failed: Option<ArmorError>,
+}
+
+impl<R: Read> DecodeReader<R> {
+    /// Read back `reader`, spelled out in `encoding`
+    pub fn new(encoding: Encoding, reader: R) -> DecodeReader<R> {
+        DecodeReader {
+            reader,
+            decoder: Decoder::new(encoding),
+            buf: Vec::new(),
+            pos: 0,
+            done: false,
+            failed: None,
+        }
+    }
+
+    /// Get a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Decode more of the wrapped reader into the buffer, which has all been read
+    fn fill_buf(&mut self) -> io::Result<()> {
+        let mut text = [0; 1024];
+        self.buf.clear();
+        self.pos = 0;
+        let n = self.reader.read(&mut text)?;
+        let result = if n == 0 {
+            self.done = true;
+            self.decoder.finish(&mut self.buf)
+        } else {
+            self.decoder.decode(&text[..n], &mut self.buf)
+        };
+        if let Err(err) = result {
+            self.failed = Some(err);
+            return Err(err.into());
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Some(err) = self.failed {
+            return Err(err.into());
+        }
+        while self.pos == self.buf.len() && !self.done && !buf.is_empty() {
+            self.fill_buf()?;
+        }
+
+        let n = buf.len().min(self.buf.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
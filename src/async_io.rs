@@ -0,0 +1,232 @@
+//! Munging adapters for async readers and writers.
+//!
+//! These are the async counterparts of [`XorDataReader`](crate::xorcism::XorDataReader) and
+//! [`XorDataWriter`](crate::xorcism::XorDataWriter). They implement the `futures-io` traits
+//! with the `futures-io` feature, and tokio's with the `tokio` feature.
+//!
+//! The keystream advances exactly as it would with the blocking adapters: past the bytes
+//! each read returns, and past the bytes the wrapped writer accepts. A poll which returns
+//! `Pending` or an error leaves it where it was, so a cancelled read or write can be retried
+//! without the data going out of step with the key.
+//!
+//! The wrapped reader or writer must be [`Unpin`]; pin anything else in a [`Box`] first.
+
+use crate::keystream::{xor_words, Keystream};
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The most data munged for one write, so the keystream buffered ahead stays small
+const WRITE_CHUNK: usize = 8 * 1024;
+
+/// An async reader which munges everything read from the wrapped reader.
+///
+/// Created by [`Xorcism::async_reader`](crate::xorcism::Xorcism::async_reader) or
+/// [`Keystream::async_reader`].
+pub struct AsyncXorDataReader<Stream, DataReader> {
+    xor: Stream,
+    data: DataReader,
+}
+
+impl<Stream, DataReader> AsyncXorDataReader<Stream, DataReader> {
+    pub(crate) fn new(xor: Stream, data: DataReader) -> Self {
+        AsyncXorDataReader { xor, data }
+    }
+
+    /// Get a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &DataReader {
+        &self.data
+    }
+
+    /// Get a mutable reference to the wrapped reader.
+    ///
+    /// Reading from it directly bypasses the munger, so the key position no longer
+    /// matches the stream position.
+    pub fn get_mut(&mut self) -> &mut DataReader {
+        &mut self.data
+    }
+
+    /// Unwrap this reader, returning the wrapped reader and the munger at its current key position.
+    pub fn into_inner(self) -> (DataReader, Stream) {
+        (self.data, self.xor)
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<Stream, DataReader> futures_io::AsyncRead for AsyncXorDataReader<Stream, DataReader>
+where
+    Stream: Keystream + Unpin,
+    DataReader: futures_io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.data).poll_read(cx, buf);
+        if let Poll::Ready(Ok(i)) = poll {
+            this.xor.munge_in_place(&mut buf[..i]);
+        }
+
+        poll
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<Stream, DataReader> tokio::io::AsyncRead for AsyncXorDataReader<Stream, DataReader>
+where
+    Stream: Keystream + Unpin,
+    DataReader: tokio::io::AsyncRead + Unpin,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let start = buf.filled().len();
+        let poll = Pin::new(&mut this.data).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            this.xor.munge_in_place(&mut buf.filled_mut()[start..]);
+        }
+
+        poll
+    }
+}
+
+/// An async writer which munges everything written to it before passing it on to the
+/// wrapped writer.
+///
+/// Created by [`Xorcism::async_writer`](crate::xorcism::Xorcism::async_writer) or
+/// [`Keystream::async_writer`].
+pub struct AsyncXorDataWriter<Stream, DataWriter> {
+    xor: Stream,
+    data: DataWriter,
+    /// keystream taken from `xor` for bytes the wrapped writer hasn't accepted yet, so a
+    /// write which returns `Pending` can be retried without rewinding the keystream
+    keystream: Vec<u8>,
+    buf: Vec<u8>, // scratch space for munged output, reused between writes
+}
+
+impl<Stream, DataWriter> AsyncXorDataWriter<Stream, DataWriter> {
+    pub(crate) fn new(xor: Stream, data: DataWriter) -> Self {
+        AsyncXorDataWriter {
+            xor,
+            data,
+            keystream: Vec::new(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &DataWriter {
+        &self.data
+    }
+
+    /// Get a mutable reference to the wrapped writer.
+    ///
+    /// Writing to it directly bypasses the munger, so the key position no longer
+    /// matches the stream position.
+    pub fn get_mut(&mut self) -> &mut DataWriter {
+        &mut self.data
+    }
+}
+
+impl<Stream, DataWriter> AsyncXorDataWriter<Stream, DataWriter>
+where
+    Stream: Keystream + Unpin,
+{
+    /// Unwrap this writer, returning the wrapped writer and the munger at its current key position.
+    ///
+    /// The wrapped writer is not flushed.
+    pub fn into_inner(mut self) -> (DataWriter, Stream) {
+        if !self.keystream.is_empty() {
+            let position = self.xor.position() - self.keystream.len() as u64;
+            self.xor.seek_to(position);
+        }
+
+        (self.data, self.xor)
+    }
+
+    /// Munge as much of `data` as one write takes into the scratch buffer
+    fn munge_chunk(&mut self, data: &[u8]) {
+        let len = data.len().min(WRITE_CHUNK);
+        let have = self.keystream.len();
+        if have < len {
+            self.keystream.resize(len, 0);
+            self.xor.fill(&mut self.keystream[have..]);
+        }
+
+        self.buf.clear();
+        self.buf.extend_from_slice(&data[..len]);
+        xor_words(&mut self.buf, &self.keystream[..len]);
+    }
+
+    /// Let go of the keystream for the `n` bytes the wrapped writer accepted
+    fn consume(&mut self, n: usize) {
+        self.keystream.drain(..n);
+    }
+}
+
+#[cfg(feature = "futures-io")]
+impl<Stream, DataWriter> futures_io::AsyncWrite for AsyncXorDataWriter<Stream, DataWriter>
+where
+    Stream: Keystream + Unpin,
+    DataWriter: futures_io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.munge_chunk(data);
+        let poll = Pin::new(&mut this.data).poll_write(cx, &this.buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.consume(n);
+        }
+
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().data).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().data).poll_close(cx)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<Stream, DataWriter> tokio::io::AsyncWrite for AsyncXorDataWriter<Stream, DataWriter>
+where
+    Stream: Keystream + Unpin,
+    DataWriter: tokio::io::AsyncWrite + Unpin,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        data: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.munge_chunk(data);
+        let poll = Pin::new(&mut this.data).poll_write(cx, &this.buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.consume(n);
+        }
+
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().data).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().data).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,208 @@
+//! Authenticated munging, which notices wrong keys and tampering.
+//!
+//! XOR munges back with any key at all, and flipping a bit of munged data flips the same bit
+//! of the plaintext, so on its own it can't tell good data from garbage. In authenticated
+//! mode the writer appends an HMAC-SHA-256 tag of the plaintext, and the reader checks it once
+//! it reaches the end of the data:
+//!
+//! | Size        | Field                                  |
+//! |-------------|----------------------------------------|
+//! | *           | munged payload                         |
+//! | [`TAG_LEN`] | HMAC-SHA-256 of the plaintext payload  |
+//!
+//! The reader hands out plaintext before it has seen the tag, so a failed check comes at
+//! EOF, as an error in place of the end of the data. Anything which reads to the end, like
+//! [`std::io::copy`] or [`Read::read_to_end`], fails loudly instead of quietly producing
+//! garbage; anything which acts on the data before then should buffer it first.
+
+use crate::hold_back::HoldBack;
+use crate::keystream::Keystream;
+use crate::sha256::{hmac_sha256, HmacSha256, DIGEST_LEN};
+use crate::xorcism::{XorDataReader, XorDataWriter};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+/// The length of the tag appended to the payload, in bytes
+pub const TAG_LEN: usize = DIGEST_LEN;
+
+/// Reasons authenticated data fails its check
+///
+/// Wrapped in an [`io::Error`] of kind [`io::ErrorKind::PermissionDenied`], which nothing
+/// else in this crate returns. The wrapped reader can still return that kind itself;
+/// [`is_authentication_error`] tells the two apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthenticationError {
+    /// The data ends before a whole tag
+    Truncated,
+    /// The tag doesn't match: the key is wrong, or the data was changed
+    TagMismatch,
+}
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthenticationError::Truncated => write!(f, "authenticated data is truncated"),
+            AuthenticationError::TagMismatch => {
+                write!(f, "authentication failed: wrong key or tampered data")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AuthenticationError {}
+
+impl From<AuthenticationError> for io::Error {
+    fn from(err: AuthenticationError) -> Self {
+        io::Error::new(io::ErrorKind::PermissionDenied, err)
+    }
+}
+
+/// Whether `err` is an authentication failure from an [`AuthenticatedReader`]
+pub fn is_authentication_error(err: &io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|err| err.is::<AuthenticationError>())
+}
+
+/// The key to authenticate with when munging with `key`
+///
+/// The tag must not be made with the munging key itself, or the two would leak into each
+/// other, so this derives a separate key from it.
+pub fn mac_key(key: &[u8]) -> [u8; DIGEST_LEN] {
+    hmac_sha256(key, b"xorcism authentication key")
+}
+
+/// Compare two tags, taking as long whatever bytes they differ in
+fn tags_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A writer which munges everything written to it, and appends a tag of the plaintext
+///
+/// The tag is only written by [`AuthenticatedWriter::finish`]; data which is dropped without
+/// finishing won't read back.
+pub struct AuthenticatedWriter<Stream, DataWriter> {
+    writer: XorDataWriter<Stream, DataWriter>,
+    mac: HmacSha256,
+}
+
+impl<Stream, DataWriter> AuthenticatedWriter<Stream, DataWriter>
+where
+    Stream: Keystream,
+    DataWriter: Write,
+{
+    /// Munge with `stream` into `writer`, authenticating with `mac_key`
+    ///
+    /// For a [`Xorcism`](crate::xorcism::Xorcism), [`mac_key`] derives a suitable key from
+    /// its own.
+    pub fn new(
+        stream: Stream,
+        mac_key: &[u8],
+        writer: DataWriter,
+    ) -> AuthenticatedWriter<Stream, DataWriter> {
+        AuthenticatedWriter {
+            writer: stream.writer(writer),
+            mac: HmacSha256::new(mac_key),
+        }
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &DataWriter {
+        self.writer.get_ref()
+    }
+
+    /// Write the tag, returning the wrapped writer
+    ///
+    /// The wrapped writer is flushed.
+    pub fn finish(self) -> io::Result<DataWriter> {
+        let (mut writer, _) = self.writer.into_inner();
+        writer.write_all(&self.mac.finalize())?;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+}
+
+impl<Stream, DataWriter> Write for AuthenticatedWriter<Stream, DataWriter>
+where
+    Stream: Keystream,
+    DataWriter: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.mac.update(&buf[..n]);
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A reader which munges everything read through it, and checks the tag at the end.
+///
+/// Reaching the end fails with an [`AuthenticationError`] if the tag doesn't match.
+pub struct AuthenticatedReader<Stream, DataReader> {
+    reader: XorDataReader<Stream, HoldBack<DataReader>>,
+    mac: HmacSha256,
+    verified: bool,
+}
+
+impl<Stream, DataReader> AuthenticatedReader<Stream, DataReader>
+where
+    Stream: Keystream,
+    DataReader: Read,
+{
+    /// Munge `reader` with `stream`, checking it against the tag made with `mac_key`
+    pub fn new(
+        stream: Stream,
+        mac_key: &[u8],
+        reader: DataReader,
+    ) -> AuthenticatedReader<Stream, DataReader> {
+        AuthenticatedReader {
+            reader: stream.reader(HoldBack::new(reader, TAG_LEN)),
+            mac: HmacSha256::new(mac_key),
+            verified: false,
+        }
+    }
+
+    /// Get a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &DataReader {
+        self.reader.get_ref().get_ref()
+    }
+
+    /// Check the tag against everything read
+    fn verify(&self) -> Result<(), AuthenticationError> {
+        let tag = self.reader.get_ref().trailer();
+        if tag.len() < TAG_LEN {
+            return Err(AuthenticationError::Truncated);
+        }
+        if !tags_match(&self.mac.clone().finalize(), tag) {
+            return Err(AuthenticationError::TagMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl<Stream, DataReader> Read for AuthenticatedReader<Stream, DataReader>
+where
+    Stream: Keystream,
+    DataReader: Read,
+{
+    /// A tag which fails its check fails every read at the end again, so the failure can't
+    /// be skipped past by reading again.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n == 0 && !buf.is_empty() && !self.verified {
+            self.verify()?;
+            self.verified = true;
+        }
+        self.mac.update(&buf[..n]);
+
+        Ok(n)
+    }
+}
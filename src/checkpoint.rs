@@ -0,0 +1,177 @@
+//! Checkpoints of how far a munger has got, for carrying on after a restart.
+//!
+//! A checkpoint records the keystream position and a fingerprint of the key, but not the key
+//! itself, so it can be stored alongside a half-finished upload without giving the key away.
+//! Resuming checks the fingerprint, so a checkpoint can't silently carry on with the wrong
+//! key. Encoded, it is a few fixed fields:
+//!
+//! | Offset | Size | Field                                        |
+//! |--------|------|----------------------------------------------|
+//! | 0      | 1    | format version, currently 1                  |
+//! | 1      | 1    | flags: bit 0 is set if the key follows       |
+//! | 2      | 8    | key fingerprint                              |
+//! | 10     | 8    | key length                                   |
+//! | 18     | 8    | keystream position                           |
+//! | 26     | *    | the key, only if explicitly asked for        |
+//!
+//! Integers are big-endian.
+
+use crate::sha256::hmac_sha256;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The format version written by [`Checkpoint::to_bytes`]
+pub const VERSION: u8 = 1;
+
+/// The length of an encoded checkpoint without its key
+pub const CHECKPOINT_LEN: usize = 26;
+
+/// The length of a key fingerprint in bytes
+pub const FINGERPRINT_LEN: usize = 8;
+
+/// The flag set when the key follows the checkpoint
+const KEY_INCLUDED: u8 = 1;
+
+/// A short, one-way fingerprint of `key`, to tell whether a key is the right one
+///
+/// It is a truncated HMAC keyed with the key itself, so it gives away nothing about the key
+/// beyond letting a guess be checked, which munged data usually allows anyway.
+pub fn fingerprint(key: &[u8]) -> [u8; FINGERPRINT_LEN] {
+    let mut fingerprint = [0; FINGERPRINT_LEN];
+    fingerprint.copy_from_slice(&hmac_sha256(key, b"xorcism key fingerprint")[..FINGERPRINT_LEN]);
+    fingerprint
+}
+
+/// Reasons a checkpoint can't be read or resumed from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CheckpointError {
+    /// The checkpoint was written by a newer version of the format
+    UnsupportedVersion(u8),
+    /// The checkpoint has flags this version doesn't know
+    UnknownFlags(u8),
+    /// The data ends before the checkpoint does
+    Truncated,
+    /// There is more data after the checkpoint
+    TrailingBytes,
+    /// The key doesn't match the checkpoint's fingerprint
+    WrongKey,
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {}", version)
+            }
+            CheckpointError::UnknownFlags(flags) => {
+                write!(f, "unknown checkpoint flags {:#04x}", flags)
+            }
+            CheckpointError::Truncated => write!(f, "checkpoint is truncated"),
+            CheckpointError::TrailingBytes => write!(f, "unexpected data after checkpoint"),
+            CheckpointError::WrongKey => write!(f, "key does not match the checkpoint"),
+        }
+    }
+}
+
+impl core::error::Error for CheckpointError {}
+
+/// How far a munger has got, and which key it had
+///
+/// Made by [`Xorcism::checkpoint`](crate::xorcism::Xorcism::checkpoint), and carried on from
+/// by [`Xorcism::resume`](crate::xorcism::Xorcism::resume).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// The [`fingerprint`] of the key
+    pub fingerprint: [u8; FINGERPRINT_LEN],
+    /// The length of the key in bytes
+    pub key_len: u64,
+    /// The offset into the keystream of the next byte to be munged
+    pub position: u64,
+}
+
+impl Checkpoint {
+    /// A checkpoint at `position` in the keystream of `key`
+    pub fn new(key: &[u8], position: u64) -> Checkpoint {
+        Checkpoint {
+            fingerprint: fingerprint(key),
+            key_len: key.len() as u64,
+            position,
+        }
+    }
+
+    /// Whether `key` is the key the checkpoint was made with, as far as the fingerprint
+    /// can tell
+    pub fn matches(&self, key: &[u8]) -> bool {
+        key.len() as u64 == self.key_len && fingerprint(key) == self.fingerprint
+    }
+
+    /// Encode the checkpoint, without the key
+    pub fn to_bytes(&self) -> [u8; CHECKPOINT_LEN] {
+        self.encode(0)
+    }
+
+    /// Encode the checkpoint followed by the key itself, so it can be resumed from alone
+    ///
+    /// Anyone who can read the result can munge with the key, so it needs keeping as safe as
+    /// the key. Fails if `key` isn't the key the checkpoint was made with.
+    #[cfg(feature = "alloc")]
+    pub fn to_bytes_with_key(&self, key: &[u8]) -> Result<Vec<u8>, CheckpointError> {
+        if !self.matches(key) {
+            return Err(CheckpointError::WrongKey);
+        }
+
+        let mut bytes = Vec::with_capacity(CHECKPOINT_LEN + key.len());
+        bytes.extend_from_slice(&self.encode(KEY_INCLUDED));
+        bytes.extend_from_slice(key);
+        Ok(bytes)
+    }
+
+    fn encode(&self, flags: u8) -> [u8; CHECKPOINT_LEN] {
+        let mut bytes = [0; CHECKPOINT_LEN];
+        bytes[0] = VERSION;
+        bytes[1] = flags;
+        bytes[2..10].copy_from_slice(&self.fingerprint);
+        bytes[10..18].copy_from_slice(&self.key_len.to_be_bytes());
+        bytes[18..26].copy_from_slice(&self.position.to_be_bytes());
+        bytes
+    }
+
+    /// Decode a checkpoint, along with its key if it was encoded with one
+    ///
+    /// An included key is checked against the fingerprint, to catch corruption.
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Checkpoint, Option<&[u8]>), CheckpointError> {
+        let (header, rest) = bytes
+            .split_first_chunk::<CHECKPOINT_LEN>()
+            .ok_or(CheckpointError::Truncated)?;
+        if header[0] != VERSION {
+            return Err(CheckpointError::UnsupportedVersion(header[0]));
+        }
+        if header[1] & !KEY_INCLUDED != 0 {
+            return Err(CheckpointError::UnknownFlags(header[1]));
+        }
+
+        let checkpoint = Checkpoint {
+            fingerprint: header[2..10].try_into().unwrap(),
+            key_len: u64::from_be_bytes(header[10..18].try_into().unwrap()),
+            position: u64::from_be_bytes(header[18..26].try_into().unwrap()),
+        };
+        let included = header[1] & KEY_INCLUDED != 0;
+        let key_len = if included { checkpoint.key_len } else { 0 };
+        if (rest.len() as u64) < key_len {
+            return Err(CheckpointError::Truncated);
+        }
+        if rest.len() as u64 > key_len {
+            return Err(CheckpointError::TrailingBytes);
+        }
+
+        if !included {
+            return Ok((checkpoint, None));
+        }
+        if !checkpoint.matches(rest) {
+            return Err(CheckpointError::WrongKey);
+        }
+        Ok((checkpoint, Some(rest)))
+    }
+}
@@ -0,0 +1,357 @@
+//! A self-describing file format for munged data.
+//!
+//! Raw munged bytes say nothing about how they were made. A container starts with a header
+//! recording everything needed to munge them back except the key itself, and ends with a
+//! footer which checks that they were:
+//!
+//! | Offset | Size | Field                                                  |
+//! |--------|------|--------------------------------------------------------|
+//! | 0      | 4    | magic, `XRCM`                                          |
+//! | 4      | 1    | format version, currently 1                            |
+//! | 5      | 1    | salt length, 0 if there is no salt                     |
+//! | 6      | 8    | key fingerprint                                        |
+//! | 14     | 4    | key length                                             |
+//! | 18     | 8    | starting offset into the keystream                     |
+//! | 26     | *    | salt                                                   |
+//! | *      | *    | munged payload                                         |
+//! | end-12 | 8    | payload length                                         |
+//! | end-4  | 4    | CRC-32 of the plaintext payload                        |
+//!
+//! Integers are big-endian. The payload length and checksum come last so that containers
+//! can be written in one pass to pipes and sockets, which can't go back to fill in a header.
+
+pub use crate::checkpoint::{fingerprint, FINGERPRINT_LEN};
+
+use crate::crc32::Crc32;
+use crate::hold_back::HoldBack;
+use crate::xorcism::{XorDataReader, XorDataWriter, Xorcism};
+use std::{
+    fmt,
+    io::{self, Read, Write},
+};
+
+/// The bytes every container starts with
+pub const MAGIC: [u8; 4] = *b"XRCM";
+
+/// The format version written by [`ContainerWriter`]
+pub const VERSION: u8 = 1;
+
+/// The length of a header without its salt
+const HEADER_LEN: usize = 26;
+
+/// The length of the footer
+const FOOTER_LEN: usize = 12;
+
+/// Reasons a container can't be written or read
+///
+/// Wrapped in an [`io::Error`], of kind [`io::ErrorKind::InvalidData`] when reading and
+/// [`io::ErrorKind::InvalidInput`] when writing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ContainerError {
+    /// The data doesn't start with [`MAGIC`], so isn't a container
+    BadMagic,
+    /// The container was written by a newer version of the format
+    UnsupportedVersion(u8),
+    /// The key doesn't match the fingerprint in the header
+    WrongKey,
+    /// The data ends before the footer
+    Truncated,
+    /// The payload is a different length from the one recorded in the footer
+    LengthMismatch {
+        /// The length recorded in the footer
+        expected: u64,
+        /// The length actually read
+        actual: u64,
+    },
+    /// The munged-back payload doesn't match the checksum in the footer
+    ChecksumMismatch,
+    /// The salt is too long to record
+    SaltTooLong(usize),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::BadMagic => write!(f, "not a xorcism container"),
+            ContainerError::UnsupportedVersion(version) => {
+                write!(f, "unsupported container version {}", version)
+            }
+            ContainerError::WrongKey => write!(f, "key does not match the container"),
+            ContainerError::Truncated => write!(f, "container is truncated"),
+            ContainerError::LengthMismatch { expected, actual } => write!(
+                f,
+                "container payload is {} bytes, but should be {}",
+                actual, expected
+            ),
+            ContainerError::ChecksumMismatch => write!(f, "container checksum does not match"),
+            ContainerError::SaltTooLong(len) => {
+                write!(f, "salt of {} bytes is longer than 255", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+impl From<ContainerError> for io::Error {
+    fn from(err: ContainerError) -> Self {
+        let kind = match err {
+            ContainerError::SaltTooLong(_) => io::ErrorKind::InvalidInput,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+/// What a container's header records
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    /// The format version
+    pub version: u8,
+    /// The [`fingerprint`] of the key
+    pub fingerprint: [u8; FINGERPRINT_LEN],
+    /// The length of the key in bytes
+    pub key_len: u32,
+    /// The keystream position the payload was munged from
+    pub offset: u64,
+    /// The salt the key was derived with, if it came from a passphrase
+    pub salt: Option<Vec<u8>>,
+}
+
+impl Header {
+    /// Read and check a header, leaving `reader` at the start of the payload
+    ///
+    /// This is all that's needed to find the salt to derive a key with, before opening the
+    /// container with [`ContainerReader::new`].
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Header> {
+        let mut header = [0; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(ContainerError::BadMagic.into());
+        }
+        let version = header[4];
+        if version != VERSION {
+            return Err(ContainerError::UnsupportedVersion(version).into());
+        }
+
+        let salt = match header[5] {
+            0 => None,
+            len => {
+                let mut salt = vec![0; len as usize];
+                reader.read_exact(&mut salt)?;
+                Some(salt)
+            }
+        };
+
+        Ok(Header {
+            version,
+            fingerprint: header[6..14].try_into().unwrap(),
+            key_len: u32::from_be_bytes(header[14..18].try_into().unwrap()),
+            offset: u64::from_be_bytes(header[18..26].try_into().unwrap()),
+            salt,
+        })
+    }
+
+    /// Write the header
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let salt = self.salt.as_deref().unwrap_or_default();
+        let salt_len =
+            u8::try_from(salt.len()).map_err(|_| ContainerError::SaltTooLong(salt.len()))?;
+
+        let mut header = Vec::with_capacity(HEADER_LEN + salt.len());
+        header.extend_from_slice(&MAGIC);
+        header.push(self.version);
+        header.push(salt_len);
+        header.extend_from_slice(&self.fingerprint);
+        header.extend_from_slice(&self.key_len.to_be_bytes());
+        header.extend_from_slice(&self.offset.to_be_bytes());
+        header.extend_from_slice(salt);
+        writer.write_all(&header)
+    }
+
+    /// Whether `key` is the one the container was written with
+    pub fn matches(&self, key: &[u8]) -> bool {
+        key.len() == self.key_len as usize && fingerprint(key) == self.fingerprint
+    }
+}
+
+/// A writer which munges everything written to it into a container.
+///
+/// The footer is only written by [`ContainerWriter::finish`]; a container which is dropped
+/// without finishing is truncated, and won't read back.
+pub struct ContainerWriter<Key, DataWriter> {
+    writer: XorDataWriter<Xorcism<Key>, DataWriter>,
+    crc: Crc32,
+    len: u64,
+}
+
+impl<Key, DataWriter> ContainerWriter<Key, DataWriter>
+where
+    Key: AsRef<[u8]>,
+    DataWriter: Write,
+{
+    /// Write a container header to `writer`, ready for the payload
+    ///
+    /// The payload is munged from the munger's current position, which the header records.
+    /// Pass the salt if the key was derived from a passphrase, so it can be derived again.
+    pub fn new(
+        xorcism: Xorcism<Key>,
+        salt: Option<&[u8]>,
+        mut writer: DataWriter,
+    ) -> io::Result<ContainerWriter<Key, DataWriter>> {
+        let key = xorcism.key();
+        let header = Header {
+            version: VERSION,
+            fingerprint: fingerprint(key),
+            key_len: u32::try_from(key.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "key is too long"))?,
+            offset: xorcism.position(),
+            salt: salt.map(<[u8]>::to_vec),
+        };
+        header.write_to(&mut writer)?;
+
+        Ok(ContainerWriter {
+            writer: xorcism.writer(writer),
+            crc: Crc32::new(),
+            len: 0,
+        })
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &DataWriter {
+        self.writer.get_ref()
+    }
+
+    /// Write the footer, returning the wrapped writer
+    ///
+    /// The wrapped writer is flushed.
+    pub fn finish(self) -> io::Result<DataWriter> {
+        let (mut writer, _) = self.writer.into_inner();
+        let mut footer = [0; FOOTER_LEN];
+        footer[..8].copy_from_slice(&self.len.to_be_bytes());
+        footer[8..].copy_from_slice(&self.crc.value().to_be_bytes());
+        writer.write_all(&footer)?;
+        writer.flush()?;
+
+        Ok(writer)
+    }
+}
+
+impl<Key, DataWriter> Write for ContainerWriter<Key, DataWriter>
+where
+    Key: AsRef<[u8]>,
+    DataWriter: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.crc.update(&buf[..n]);
+        self.len += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A reader which munges a container's payload back, checking it against the footer at the end.
+///
+/// Reaching the end of the payload fails with an [`io::ErrorKind::InvalidData`] error if the
+/// payload doesn't match the footer, so corruption can't go unnoticed by anything which
+/// reads to the end.
+pub struct ContainerReader<Key, DataReader> {
+    header: Header,
+    reader: XorDataReader<Xorcism<Key>, HoldBack<DataReader>>,
+    crc: Crc32,
+    len: u64,
+    verified: bool,
+}
+
+impl<Key, DataReader> ContainerReader<Key, DataReader>
+where
+    Key: AsRef<[u8]>,
+    DataReader: Read,
+{
+    /// Read the header from `reader` and open the container with `xorcism`'s key
+    pub fn open(
+        xorcism: Xorcism<Key>,
+        mut reader: DataReader,
+    ) -> io::Result<ContainerReader<Key, DataReader>> {
+        let header = Header::read_from(&mut reader)?;
+        ContainerReader::new(header, xorcism, reader)
+    }
+
+    /// Open a container whose header has already been read from `reader`
+    ///
+    /// Fails with [`ContainerError::WrongKey`] if `xorcism`'s key isn't the one the
+    /// container was written with. The munger is moved to the header's starting offset.
+    pub fn new(
+        header: Header,
+        mut xorcism: Xorcism<Key>,
+        reader: DataReader,
+    ) -> io::Result<ContainerReader<Key, DataReader>> {
+        if !header.matches(xorcism.key()) {
+            return Err(ContainerError::WrongKey.into());
+        }
+        xorcism.seek_to(header.offset);
+
+        Ok(ContainerReader {
+            header,
+            reader: xorcism.reader(HoldBack::new(reader, FOOTER_LEN)),
+            crc: Crc32::new(),
+            len: 0,
+            verified: false,
+        })
+    }
+
+    /// The container's header
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Get a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &DataReader {
+        self.reader.get_ref().get_ref()
+    }
+
+    /// Check the payload read against the footer
+    fn verify(&mut self) -> Result<(), ContainerError> {
+        let footer = self.reader.get_ref().trailer();
+        if footer.len() < FOOTER_LEN {
+            return Err(ContainerError::Truncated);
+        }
+
+        let expected = u64::from_be_bytes(footer[..8].try_into().unwrap());
+        if expected != self.len {
+            return Err(ContainerError::LengthMismatch {
+                expected,
+                actual: self.len,
+            });
+        }
+        if u32::from_be_bytes(footer[8..].try_into().unwrap()) != self.crc.value() {
+            return Err(ContainerError::ChecksumMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl<Key, DataReader> Read for ContainerReader<Key, DataReader>
+where
+    Key: AsRef<[u8]>,
+    DataReader: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n == 0 && !buf.is_empty() && !self.verified {
+            self.verify()?;
+            self.verified = true;
+        }
+        self.crc.update(&buf[..n]);
+        self.len += n as u64;
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,67 @@
+//! CRC-32 with the IEEE 802.3 polynomial, as used by zlib, PNG and Ethernet.
+//!
+//! A CRC catches accidental damage, not deliberate tampering: anyone can recompute it.
+
+/// The IEEE 802.3 polynomial, bit-reversed
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+/// The CRC of every byte value, for processing a byte at a time
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+/// An incremental CRC-32
+#[derive(Debug, Clone)]
+pub struct Crc32 {
+    crc: u32, // inverted, as the algorithm keeps it between bytes
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Crc32::new()
+    }
+}
+
+impl Crc32 {
+    /// Start a new CRC
+    pub fn new() -> Crc32 {
+        Crc32 { crc: !0 }
+    }
+
+    /// Add `data` after everything added so far
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.crc = TABLE[((self.crc ^ byte as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+    }
+
+    /// The CRC of everything added so far
+    pub fn value(&self) -> u32 {
+        !self.crc
+    }
+}
+
+/// The CRC-32 of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.value()
+}
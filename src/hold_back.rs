@@ -0,0 +1,56 @@
+//! A reader which keeps a trailer back from the data it wraps.
+
+use std::io::{self, Read};
+
+/// A reader which holds back the last `len` bytes of the wrapped reader, so that data followed
+/// by a fixed-size trailer can be streamed without knowing in advance where it ends.
+pub(crate) struct HoldBack<DataReader> {
+    data: DataReader,
+    len: usize,
+    held: Vec<u8>, // at most `len` bytes between reads; the trailer once at EOF
+}
+
+impl<DataReader> HoldBack<DataReader> {
+    pub(crate) fn new(data: DataReader, len: usize) -> Self {
+        HoldBack {
+            data,
+            len,
+            held: Vec::with_capacity(len),
+        }
+    }
+
+    /// The wrapped reader
+    pub(crate) fn get_ref(&self) -> &DataReader {
+        &self.data
+    }
+
+    /// The trailer, once the wrapped reader has reached EOF
+    ///
+    /// Shorter than the trailer length if the data was too short to hold one.
+    pub(crate) fn trailer(&self) -> &[u8] {
+        &self.held
+    }
+}
+
+impl<DataReader: Read> Read for HoldBack<DataReader> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            let n = self.data.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+
+            self.held.extend_from_slice(&buf[..n]);
+            let release = self.held.len().saturating_sub(self.len);
+            if release > 0 {
+                buf[..release].copy_from_slice(&self.held[..release]);
+                self.held.drain(..release);
+                return Ok(release);
+            }
+        }
+    }
+}
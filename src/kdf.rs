@@ -0,0 +1,88 @@
+//! Key derivation from passphrases.
+//!
+//! Passphrases are short and guessable, so they are stretched into keys with PBKDF2-HMAC-SHA-256
+//! (RFC 8018): every guess costs an attacker as many HMACs as the chosen iteration count. A
+//! random salt, stored alongside the munged data, makes each derived key unique, so guesses
+//! can't be precomputed or shared between files.
+
+use crate::sha256::{HmacSha256, DIGEST_LEN};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::SystemTime,
+};
+
+/// The iteration count used when there is no reason to choose another
+pub const DEFAULT_ITERATIONS: u32 = 100_000;
+
+/// The length of the salts made by [`random_salt`], in bytes
+#[cfg(feature = "std")]
+pub const SALT_LEN: usize = 16;
+
+/// Fill `out` with PBKDF2-HMAC-SHA-256 of `passphrase` and `salt`, iterated `iterations` times
+///
+/// An iteration count of 0 is treated as 1.
+pub fn pbkdf2_hmac_sha256(passphrase: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    let keyed = HmacSha256::new(passphrase);
+    for (block, chunk) in (1_u32..).zip(out.chunks_mut(DIGEST_LEN)) {
+        let mut hmac = keyed.clone();
+        hmac.update(salt);
+        hmac.update(&block.to_be_bytes());
+        let mut u = hmac.finalize();
+        let mut t = u;
+        for _ in 1..iterations {
+            let mut hmac = keyed.clone();
+            hmac.update(&u);
+            u = hmac.finalize();
+            for (t, u) in t.iter_mut().zip(u) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+/// Derive a key of `key_len` bytes from `passphrase` and `salt`
+///
+/// The cost of deriving the key, for the user and for anyone guessing the passphrase, grows
+/// with `iterations`; [`DEFAULT_ITERATIONS`] is a reasonable choice. The same passphrase,
+/// salt and iteration count always give the same key, ready for
+/// [`Xorcism::new`](crate::xorcism::Xorcism::new).
+#[cfg(feature = "alloc")]
+pub fn derive_key<Passphrase>(
+    passphrase: &Passphrase,
+    salt: &[u8],
+    iterations: u32,
+    key_len: usize,
+) -> Vec<u8>
+where
+    Passphrase: AsRef<[u8]> + ?Sized,
+{
+    let mut key = vec![0; key_len];
+    pbkdf2_hmac_sha256(passphrase.as_ref(), salt, iterations, &mut key);
+    key
+}
+
+/// A fresh salt, different every time
+///
+/// The salt only needs to be unique, not secret, so it comes from the randomly keyed hashers
+/// the standard library seeds from the operating system, mixed with the time.
+#[cfg(feature = "std")]
+pub fn random_salt() -> [u8; SALT_LEN] {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+
+    let mut salt = [0; SALT_LEN];
+    for (i, chunk) in salt.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(now);
+        hasher.write_usize(i);
+        chunk.copy_from_slice(&hasher.finish().to_le_bytes()[..chunk.len()]);
+    }
+    salt
+}
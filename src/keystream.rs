@@ -0,0 +1,197 @@
+//! Sources of keystream bytes to munge data with.
+//!
+//! [`Xorcism`](crate::xorcism::Xorcism) repeats a fixed key, but anything which can produce a
+//! stream of bytes and jump to any offset in it can munge data just the same, and reuse the
+//! same lazy iterator and I/O adapters.
+
+pub mod lfsr;
+pub mod prng;
+
+pub use lfsr::{FibonacciLfsr, GaloisLfsr};
+pub use prng::{SplitMix64, Xorshift64};
+
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+use crate::async_io::{AsyncXorDataReader, AsyncXorDataWriter};
+use crate::xorcism::Munge;
+#[cfg(feature = "io")]
+use crate::xorcism::{XorDataReader, XorDataWriter};
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::borrow::Borrow;
+#[cfg(feature = "io")]
+use std::io::{Read, Write};
+
+/// Size of the stack buffer the default `munge_in_place` fills with keystream.
+const FILL_LEN: usize = 512;
+
+/// XOR `src` into `dst` a `u64` word at a time, finishing any tail a byte at a time.
+pub(crate) fn xor_words(dst: &mut [u8], src: &[u8]) {
+    debug_assert_eq!(dst.len(), src.len());
+
+    let mut dst_words = dst.chunks_exact_mut(8);
+    let mut src_words = src.chunks_exact(8);
+    for (dst_word, src_word) in (&mut dst_words).zip(&mut src_words) {
+        let word = u64::from_ne_bytes(dst_word.try_into().unwrap())
+            ^ u64::from_ne_bytes(src_word.try_into().unwrap());
+        dst_word.copy_from_slice(&word.to_ne_bytes());
+    }
+
+    for (dst_byte, src_byte) in dst_words
+        .into_remainder()
+        .iter_mut()
+        .zip(src_words.remainder())
+    {
+        *dst_byte ^= src_byte;
+    }
+}
+
+/// A stream of bytes to XOR data with
+///
+/// Only [`Keystream::next_byte`], [`Keystream::seek_to`] and [`Keystream::position`] need
+/// implementing; the rest have defaults built on them, which implementations can override
+/// with something faster.
+pub trait Keystream {
+    /// The next byte of the keystream
+    fn next_byte(&mut self) -> u8;
+
+    /// Move to `offset` bytes from the start of the keystream.
+    ///
+    /// The stream continues exactly as if `offset` bytes had been taken from its start.
+    fn seek_to(&mut self, offset: u64);
+
+    /// The offset into the keystream of the next byte
+    fn position(&self) -> u64;
+
+    /// Fill `buf` with the next bytes of the keystream
+    fn fill(&mut self, buf: &mut [u8]) {
+        for byte in buf {
+            *byte = self.next_byte();
+        }
+    }
+
+    /// XOR each byte of the input buffer with the next byte of the keystream.
+    ///
+    /// Note that this is stateful: repeated calls are likely to produce different results,
+    /// even with identical inputs.
+    fn munge_in_place(&mut self, data: &mut [u8]) {
+        let mut keystream = [0; FILL_LEN];
+        for chunk in data.chunks_mut(FILL_LEN) {
+            let keystream = &mut keystream[..chunk.len()];
+            self.fill(keystream);
+            xor_words(chunk, keystream);
+        }
+    }
+
+    /// XOR each byte of `src` with the next byte of the keystream, writing the result to
+    /// `dst`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` and `dst` have different lengths.
+    fn munge_into(&mut self, src: &[u8], dst: &mut [u8]) {
+        dst.copy_from_slice(src);
+        self.munge_in_place(dst);
+    }
+
+    /// XOR each byte of the data with the next byte of the keystream.
+    ///
+    /// The returned iterator is lazy: each byte is munged as it is pulled, and the keystream
+    /// only advances past the bytes which have actually been yielded.
+    fn munge<Data, T>(&mut self, data: Data) -> Munge<'_, Self, Data::IntoIter>
+    where
+        Self: Sized,
+        Data: IntoIterator<Item = T>,
+        T: Borrow<u8>,
+    {
+        Munge::new(self, data.into_iter())
+    }
+
+    /// Wrap a reader so that everything read through it is munged.
+    #[cfg(feature = "io")]
+    fn reader<DataReader>(self, reader: DataReader) -> XorDataReader<Self, DataReader>
+    where
+        Self: Sized,
+        DataReader: Read,
+    {
+        XorDataReader::new(self, reader)
+    }
+
+    /// Wrap a writer so that everything written through it is munged.
+    #[cfg(feature = "io")]
+    fn writer<DataWriter>(self, writer: DataWriter) -> XorDataWriter<Self, DataWriter>
+    where
+        Self: Sized,
+        DataWriter: Write,
+    {
+        XorDataWriter::new(self, writer)
+    }
+
+    /// Wrap an async reader so that everything read through it is munged.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    fn async_reader<DataReader>(self, reader: DataReader) -> AsyncXorDataReader<Self, DataReader>
+    where
+        Self: Sized,
+    {
+        AsyncXorDataReader::new(self, reader)
+    }
+
+    /// Wrap an async writer so that everything written through it is munged.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    fn async_writer<DataWriter>(self, writer: DataWriter) -> AsyncXorDataWriter<Self, DataWriter>
+    where
+        Self: Sized,
+    {
+        AsyncXorDataWriter::new(self, writer)
+    }
+}
+
+impl<Stream> Keystream for &mut Stream
+where
+    Stream: Keystream + ?Sized,
+{
+    fn next_byte(&mut self) -> u8 {
+        (**self).next_byte()
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        (**self).seek_to(offset)
+    }
+
+    fn position(&self) -> u64 {
+        (**self).position()
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        (**self).fill(buf)
+    }
+
+    fn munge_in_place(&mut self, data: &mut [u8]) {
+        (**self).munge_in_place(data)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<Stream> Keystream for Box<Stream>
+where
+    Stream: Keystream + ?Sized,
+{
+    fn next_byte(&mut self) -> u8 {
+        (**self).next_byte()
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        (**self).seek_to(offset)
+    }
+
+    fn position(&self) -> u64 {
+        (**self).position()
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        (**self).fill(buf)
+    }
+
+    fn munge_in_place(&mut self, data: &mut [u8]) {
+        (**self).munge_in_place(data)
+    }
+}
@@ -0,0 +1,236 @@
+//! Linear-feedback shift registers.
+//!
+//! A register of `width` bits shifts one bit out at a time, and feeds back the XOR of its
+//! tapped bits. Taps are given as the exponents of the feedback polynomial, as datasheets
+//! list them: `[16, 14, 13, 11]` is `x^16 + x^14 + x^13 + x^11 + 1`.
+//!
+//! Each keystream byte is the next 8 bits shifted out, the first in the least significant
+//! bit. Seeking backwards restarts the register from its seed, so it costs as much as
+//! generating everything up to the target.
+
+use super::Keystream;
+use core::fmt;
+
+/// Reasons a register can be rejected by [`FibonacciLfsr::try_new`] or [`GaloisLfsr::try_new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LfsrError {
+    /// The width is not between 1 and 64 bits
+    InvalidWidth(u32),
+    /// A tap is not between 1 and the width
+    InvalidTap(u32),
+    /// There are no taps to feed back
+    NoTaps,
+    /// The seed has bits set above the width
+    SeedTooWide,
+    /// The seed is zero, which a register never leaves
+    ZeroSeed,
+}
+
+impl fmt::Display for LfsrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LfsrError::InvalidWidth(width) => {
+                write!(f, "width must be between 1 and 64 bits, not {}", width)
+            }
+            LfsrError::InvalidTap(tap) => write!(f, "tap {} is outside the register", tap),
+            LfsrError::NoTaps => write!(f, "register must have at least one tap"),
+            LfsrError::SeedTooWide => write!(f, "seed is wider than the register"),
+            LfsrError::ZeroSeed => write!(f, "seed must not be zero"),
+        }
+    }
+}
+
+impl core::error::Error for LfsrError {}
+
+/// Check the register's shape and seed
+fn validate(width: u32, taps: &[u32], seed: u64) -> Result<(), LfsrError> {
+    if !(1..=64).contains(&width) {
+        return Err(LfsrError::InvalidWidth(width));
+    }
+    if taps.is_empty() {
+        return Err(LfsrError::NoTaps);
+    }
+    if let Some(&tap) = taps.iter().find(|&&tap| tap == 0 || tap > width) {
+        return Err(LfsrError::InvalidTap(tap));
+    }
+
+    if seed & !(u64::MAX >> (64 - width)) != 0 {
+        return Err(LfsrError::SeedTooWide);
+    }
+    if seed == 0 {
+        return Err(LfsrError::ZeroSeed);
+    }
+
+    Ok(())
+}
+
+/// The shared byte-level bookkeeping of both kinds of register
+#[derive(Debug, Clone)]
+struct Register {
+    width: u32,
+    mask: u64, // tapped bits, laid out for the kind of register
+    seed: u64,
+    state: u64,
+    pos: u64,
+}
+
+impl Register {
+    fn next_byte(&mut self, step: fn(&mut Register) -> bool) -> u8 {
+        let mut byte = 0;
+        for bit in 0..8 {
+            byte |= (step(self) as u8) << bit;
+        }
+        self.pos += 1;
+        byte
+    }
+
+    fn seek_to(&mut self, offset: u64, step: fn(&mut Register) -> bool) {
+        if offset < self.pos {
+            self.state = self.seed;
+            self.pos = 0;
+        }
+        while self.pos < offset {
+            self.next_byte(step);
+        }
+    }
+}
+
+/// Shift right, feeding the parity of the tapped bits back in at the top
+fn fibonacci_step(register: &mut Register) -> bool {
+    let out = register.state & 1 != 0;
+    let feedback = (register.state & register.mask).count_ones() as u64 & 1;
+    register.state = (register.state >> 1) | (feedback << (register.width - 1));
+    out
+}
+
+/// Shift right, toggling the tapped bits whenever a one is shifted out
+fn galois_step(register: &mut Register) -> bool {
+    let out = register.state & 1 != 0;
+    register.state >>= 1;
+    if out {
+        register.state ^= register.mask;
+    }
+    out
+}
+
+/// A Fibonacci LFSR, which XORs its tapped bits together to make the bit shifted in
+///
+/// This is the external-XOR form usually drawn in hardware scramblers.
+#[derive(Debug, Clone)]
+pub struct FibonacciLfsr {
+    register: Register,
+}
+
+impl FibonacciLfsr {
+    /// Create a register of `width` bits with the feedback polynomial given by `taps`, starting
+    /// from `seed`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register is invalid. Use [`FibonacciLfsr::try_new`] for registers which
+    /// are not known to be valid.
+    pub fn new(width: u32, taps: &[u32], seed: u64) -> FibonacciLfsr {
+        match Self::try_new(width, taps, seed) {
+            Ok(lfsr) => lfsr,
+            Err(err) => panic!("invalid LFSR: {}", err),
+        }
+    }
+
+    /// Create a register of `width` bits with the feedback polynomial given by `taps`, starting
+    /// from `seed`, rejecting registers which can't work
+    pub fn try_new(width: u32, taps: &[u32], seed: u64) -> Result<FibonacciLfsr, LfsrError> {
+        validate(width, taps, seed)?;
+        // the bit shifted out is tap `width`, so tap `t` is `width - t` bits above it
+        let mask = taps.iter().fold(0, |mask, tap| mask | 1 << (width - tap));
+        Ok(FibonacciLfsr {
+            register: Register {
+                width,
+                mask,
+                seed,
+                state: seed,
+                pos: 0,
+            },
+        })
+    }
+
+    /// The current contents of the register, between keystream bytes
+    pub fn state(&self) -> u64 {
+        self.register.state
+    }
+}
+
+impl Keystream for FibonacciLfsr {
+    fn next_byte(&mut self) -> u8 {
+        self.register.next_byte(fibonacci_step)
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        self.register.seek_to(offset, fibonacci_step)
+    }
+
+    fn position(&self) -> u64 {
+        self.register.pos
+    }
+}
+
+/// A Galois LFSR, which XORs the bit shifted out into each of its tapped bits
+///
+/// This is the internal-XOR form, cheaper in software. With the same taps it has the same
+/// period as the Fibonacci form, but produces a different sequence.
+#[derive(Debug, Clone)]
+pub struct GaloisLfsr {
+    register: Register,
+}
+
+impl GaloisLfsr {
+    /// Create a register of `width` bits with the feedback polynomial given by `taps`, starting
+    /// from `seed`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the register is invalid. Use [`GaloisLfsr::try_new`] for registers which
+    /// are not known to be valid.
+    pub fn new(width: u32, taps: &[u32], seed: u64) -> GaloisLfsr {
+        match Self::try_new(width, taps, seed) {
+            Ok(lfsr) => lfsr,
+            Err(err) => panic!("invalid LFSR: {}", err),
+        }
+    }
+
+    /// Create a register of `width` bits with the feedback polynomial given by `taps`, starting
+    /// from `seed`, rejecting registers which can't work
+    pub fn try_new(width: u32, taps: &[u32], seed: u64) -> Result<GaloisLfsr, LfsrError> {
+        validate(width, taps, seed)?;
+        // after shifting, tap `t` sits at bit `t - 1`
+        let mask = taps.iter().fold(0, |mask, tap| mask | 1 << (tap - 1));
+        Ok(GaloisLfsr {
+            register: Register {
+                width,
+                mask,
+                seed,
+                state: seed,
+                pos: 0,
+            },
+        })
+    }
+
+    /// The current contents of the register, between keystream bytes
+    pub fn state(&self) -> u64 {
+        self.register.state
+    }
+}
+
+impl Keystream for GaloisLfsr {
+    fn next_byte(&mut self) -> u8 {
+        self.register.next_byte(galois_step)
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        self.register.seek_to(offset, galois_step)
+    }
+
+    fn position(&self) -> u64 {
+        self.register.pos
+    }
+}
@@ -0,0 +1,162 @@
+//! Keystreams from simple pseudorandom number generators.
+//!
+//! Each generator produces 64-bit words, and each word gives 8 keystream bytes in
+//! little-endian order. These are for scrambling, not secrecy: anyone who sees a few words
+//! of keystream can predict the rest.
+
+use super::Keystream;
+use core::fmt;
+
+/// The increment of the SplitMix64 counter, 2^64 divided by the golden ratio
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Reasons a generator can be rejected by [`Xorshift64::try_new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PrngError {
+    /// The seed is zero, which the generator never leaves
+    ZeroSeed,
+}
+
+impl fmt::Display for PrngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrngError::ZeroSeed => write!(f, "seed must not be zero"),
+        }
+    }
+}
+
+impl core::error::Error for PrngError {}
+
+/// SplitMix64, which scrambles a counter
+///
+/// Word `n` depends only on the seed and `n`, so seeking anywhere is as cheap as taking the
+/// next byte.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    seed: u64,
+    pos: u64,
+    word: [u8; 8], // the word the next byte comes from, once pos is partway through it
+}
+
+impl SplitMix64 {
+    /// Create a generator from any seed
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 {
+            seed,
+            pos: 0,
+            word: [0; 8],
+        }
+    }
+
+    /// Word `index` of the output
+    fn word(&self, index: u64) -> u64 {
+        let mut z = self
+            .seed
+            .wrapping_add(index.wrapping_add(1).wrapping_mul(GOLDEN_GAMMA));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Keystream for SplitMix64 {
+    fn next_byte(&mut self) -> u8 {
+        if self.pos.is_multiple_of(8) {
+            self.word = self.word(self.pos / 8).to_le_bytes();
+        }
+        let byte = self.word[(self.pos % 8) as usize];
+        self.pos += 1;
+        byte
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        self.pos = offset;
+        if !offset.is_multiple_of(8) {
+            self.word = self.word(offset / 8).to_le_bytes();
+        }
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+/// Marsaglia's 64-bit xorshift generator, with shifts 13, 7 and 17
+///
+/// Each word is made from the one before, so seeking backwards restarts the generator from
+/// its seed, and costs as much as generating everything up to the target.
+#[derive(Debug, Clone)]
+pub struct Xorshift64 {
+    seed: u64,
+    state: u64,
+    words: u64, // how many words have been generated since the seed
+    pos: u64,
+    word: [u8; 8], // the last word generated, which the next byte comes from partway through
+}
+
+impl Xorshift64 {
+    /// Create a generator from a seed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the seed is zero. Use [`Xorshift64::try_new`] for seeds which are not known
+    /// to be valid.
+    pub fn new(seed: u64) -> Xorshift64 {
+        match Self::try_new(seed) {
+            Ok(xorshift) => xorshift,
+            Err(err) => panic!("invalid xorshift seed: {}", err),
+        }
+    }
+
+    /// Create a generator from a seed, rejecting seeds it can't generate from
+    pub fn try_new(seed: u64) -> Result<Xorshift64, PrngError> {
+        if seed == 0 {
+            return Err(PrngError::ZeroSeed);
+        }
+
+        Ok(Xorshift64 {
+            seed,
+            state: seed,
+            words: 0,
+            pos: 0,
+            word: [0; 8],
+        })
+    }
+
+    fn generate(&mut self) {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.words += 1;
+        self.word = self.state.to_le_bytes();
+    }
+}
+
+impl Keystream for Xorshift64 {
+    fn next_byte(&mut self) -> u8 {
+        if self.pos.is_multiple_of(8) {
+            self.generate();
+        }
+        let byte = self.word[(self.pos % 8) as usize];
+        self.pos += 1;
+        byte
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        // partway through a word, that word must already have been generated
+        let words = offset / 8 + !offset.is_multiple_of(8) as u64;
+        if words < self.words {
+            self.state = self.seed;
+            self.words = 0;
+        }
+        while self.words < words {
+            self.generate();
+        }
+        self.pos = offset;
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+}
@@ -1 +1,23 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "io")]
+pub mod armor;
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+pub mod async_io;
+#[cfg(feature = "io")]
+pub mod authenticated;
+pub mod checkpoint;
+#[cfg(feature = "io")]
+pub mod container;
+pub mod crc32;
+#[cfg(feature = "io")]
+mod hold_back;
+pub mod kdf;
+pub mod keystream;
+pub mod sha256;
 pub mod xorcism;
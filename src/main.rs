@@ -1,3 +1,688 @@
-fn main() {
-    println!("hello exercism!");
+use exercism::analysis::{many_time_pad::ManyTimePad, ByteFrequencies};
+use exercism::armor::{DecodeReader, EncodeWriter, Encoding};
+use exercism::kdf;
+use exercism::xorcism::Xorcism;
+use std::{
+    env, fmt, fs,
+    io::{self, BufRead, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+const USAGE: &str = "\
+Usage: xorcism (-k TEXT | -x HEX | -f PATH | -e VAR | -p) [OPTIONS] [INPUT]
+       xorcism crack [--corpus PATH] FILE FILE...
+
+XOR a file or stdin with a repeating key. Running it twice with the same key
+restores the original data.
+
+Key, exactly one of:
+  -k, --key TEXT        use TEXT as the key
+  -x, --key-hex HEX     use the bytes spelled out by HEX as the key
+  -f, --key-file PATH   use the contents of PATH as the key
+  -e, --key-env VAR     use the value of the environment variable VAR as the key
+  -p, --passphrase      derive the key from a passphrase typed at the terminal
+
+Options:
+  -o, --output PATH     write to PATH instead of stdout
+  -i, --in-place        rewrite INPUT in place
+      --offset N        start N bytes into the keystream
+      --salt-file PATH  keep the passphrase's salt in PATH
+      --encode FORMAT   write the output as text in FORMAT
+      --decode FORMAT   read the input as text in FORMAT
+  -h, --help            print this help
+
+INPUT defaults to stdin, which can also be given as '-'.
+
+FORMAT is one of hex, base64, base64url, or pem for Base64 wrapped between
+BEGIN and END lines. Munged data is arbitrary bytes; encode it to paste it
+somewhere that only takes text, and decode it again to munge it back.
+
+A passphrase is stretched into a key with a random salt, which is needed again
+to munge the data back. The salt is read from INPUT.salt if that exists, and
+otherwise a new one is written to OUTPUT.salt, or INPUT.salt with --in-place.
+--salt-file overrides both; its salt is created if the file doesn't exist yet.
+Set XORCISM_PASSPHRASE to give the passphrase without being asked for it, and
+XORCISM_ITERATIONS to stretch it that many times instead of 100000 when making
+a new salt. The count is kept in the salt file, so it needn't be given again.
+
+'crack' interactively recovers messages which were all munged with the same
+keystream, by dragging guessed words (cribs) across them. Matches are ranked by
+how much they look like English, or like the text in PATH if --corpus is given.";
+
+const CRACK_HELP: &str = "\
+Commands:
+  drag TEXT               try TEXT at every offset of every pair of messages
+  place MSG OFFSET TEXT   declare that message MSG holds TEXT at OFFSET
+  forget OFFSET LEN       forget the keystream from OFFSET for LEN bytes
+  show                    show every message as far as the keystream is known
+  key                     show the keystream as far as it is known, in hex
+  help                    show this help
+  quit                    stop cracking
+
+In 'show', '_' marks bytes whose keystream is unknown, and '.' marks known bytes
+which aren't printable.";
+
+/// How many of the best crib matches `drag` lists
+const DRAG_RESULTS: usize = 20;
+
+/// The environment variable which gives the passphrase instead of the terminal, for scripts
+const PASSPHRASE_VAR: &str = "XORCISM_PASSPHRASE";
+
+/// The environment variable which overrides how many times new passphrases are stretched
+const ITERATIONS_VAR: &str = "XORCISM_ITERATIONS";
+
+/// The length of keys derived from passphrases
+const PASSPHRASE_KEY_LEN: usize = 64;
+
+/// Where passphrases are typed
+#[cfg(unix)]
+const TERMINAL: &str = "/dev/tty";
+#[cfg(not(unix))]
+const TERMINAL: &str = "CONIN$";
+
+/// Where the key comes from
+enum KeySource {
+    Literal(String),
+    Hex(String),
+    File(PathBuf),
+    Env(String),
+    Passphrase,
+}
+
+impl KeySource {
+    /// Load the key, along with any salt made for it which still needs writing out
+    fn load(&self, options: &Options) -> Result<(Vec<u8>, Option<NewSalt>), Error> {
+        let key = match self {
+            KeySource::Literal(key) => Ok(key.as_bytes().to_vec()),
+            KeySource::Hex(hex) => parse_hex(hex),
+            KeySource::File(path) => fs::read(path)
+                .map_err(|err| Error::Run(format!("reading key file {}: {}", path.display(), err))),
+            KeySource::Env(var) => env::var(var)
+                .map(String::into_bytes)
+                .map_err(|err| Error::Run(format!("reading key from ${}: {}", var, err))),
+            KeySource::Passphrase => return passphrase_key(options),
+        };
+
+        key.map(|key| (key, None))
+    }
+}
+
+/// Where a passphrase's salt is kept
+enum SaltFile {
+    /// The data was munged before, with the salt in this file
+    Read(PathBuf),
+    /// The data is being munged for the first time, and a new salt goes in this file
+    Create(PathBuf),
+}
+
+/// A salt made for a new passphrase, written out only once there is an output for it to go
+/// with, so a run which fails early doesn't leave a salt behind
+struct NewSalt {
+    path: PathBuf,
+    iterations: u32,
+    salt: Vec<u8>,
+}
+
+impl NewSalt {
+    /// Write the iteration count, big-endian, followed by the salt
+    fn write(&self) -> Result<(), Error> {
+        let mut contents = self.iterations.to_be_bytes().to_vec();
+        contents.extend_from_slice(&self.salt);
+        fs::write(&self.path, contents).map_err(describe(&self.path))
+    }
+}
+
+/// Read the iteration count and salt written by [`NewSalt::write`]
+fn read_salt(path: &Path) -> Result<(u32, Vec<u8>), Error> {
+    let contents = fs::read(path).map_err(describe(path))?;
+    let (iterations, salt) = contents
+        .split_first_chunk()
+        .ok_or_else(|| Error::Run(format!("{}: salt file is truncated", path.display())))?;
+
+    Ok((u32::from_be_bytes(*iterations), salt.to_vec()))
+}
+
+/// What the command line asked for
+enum Command {
+    Help,
+    Munge(Options),
+    Crack(CrackOptions),
+}
+
+struct CrackOptions {
+    paths: Vec<PathBuf>,
+    corpus: Option<PathBuf>,
+}
+
+struct Options {
+    key: KeySource,
+    input: Option<PathBuf>,
+    output: Option<PathBuf>,
+    in_place: bool,
+    offset: u64,
+    salt_file: Option<PathBuf>,
+    encode: Option<Encoding>,
+    decode: Option<Encoding>,
+}
+
+enum Error {
+    /// The command line was wrong; the usage is worth showing
+    Usage(String),
+    /// Something went wrong while running
+    Run(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Usage(msg) | Error::Run(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Run(err.to_string())
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(Error::Usage("hex key has an odd number of digits".into()));
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| Error::Usage(format!("invalid hex key: {:?}", hex)))
+        })
+        .collect()
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Command, Error> {
+    let mut args = args.peekable();
+    if args.peek().map(String::as_str) == Some("crack") {
+        args.next();
+        return parse_crack_args(args);
+    }
+
+    parse_munge_args(args)
+}
+
+fn parse_crack_args(mut args: impl Iterator<Item = String>) -> Result<Command, Error> {
+    let mut paths = Vec::new();
+    let mut corpus = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--corpus" => {
+                let path = args
+                    .next()
+                    .ok_or_else(|| Error::Usage(format!("{} needs a value", arg)))?;
+                corpus = Some(PathBuf::from(path));
+            }
+            _ if arg.starts_with('-') => {
+                return Err(Error::Usage(format!("unknown option: {}", arg)))
+            }
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+
+    if paths.len() < 2 {
+        return Err(Error::Usage("crack needs at least two FILEs".into()));
+    }
+
+    Ok(Command::Crack(CrackOptions { paths, corpus }))
+}
+
+fn parse_munge_args(mut args: impl Iterator<Item = String>) -> Result<Command, Error> {
+    let mut key = None;
+    let mut input = None;
+    let mut output = None;
+    let mut in_place = false;
+    let mut offset = 0;
+    let mut salt_file = None;
+    let mut encode = None;
+    let mut decode = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| Error::Usage(format!("{} needs a value", name)))
+        };
+        let mut set_key = |source: KeySource| {
+            if key.replace(source).is_some() {
+                return Err(Error::Usage("only one key may be given".into()));
+            }
+            Ok(())
+        };
+
+        let encoding = |name: String| {
+            name.parse::<Encoding>()
+                .map_err(|err| Error::Usage(err.to_string()))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-k" | "--key" => set_key(KeySource::Literal(value(&arg)?))?,
+            "-x" | "--key-hex" => set_key(KeySource::Hex(value(&arg)?))?,
+            "-f" | "--key-file" => set_key(KeySource::File(value(&arg)?.into()))?,
+            "-e" | "--key-env" => set_key(KeySource::Env(value(&arg)?))?,
+            "-p" | "--passphrase" => set_key(KeySource::Passphrase)?,
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg)?)),
+            "-i" | "--in-place" => in_place = true,
+            "--salt-file" => salt_file = Some(PathBuf::from(value(&arg)?)),
+            "--encode" => encode = Some(encoding(value(&arg)?)?),
+            "--decode" => decode = Some(encoding(value(&arg)?)?),
+            "--offset" => {
+                let n = value(&arg)?;
+                offset = n
+                    .parse()
+                    .map_err(|_| Error::Usage(format!("invalid offset: {:?}", n)))?;
+            }
+            "-" => input = Some(arg),
+            _ if arg.starts_with('-') => {
+                return Err(Error::Usage(format!("unknown option: {}", arg)))
+            }
+            _ if input.is_some() => {
+                return Err(Error::Usage(format!("unexpected argument: {}", arg)))
+            }
+            _ => input = Some(arg),
+        }
+    }
+
+    let key = key.ok_or_else(|| Error::Usage("a key is required".into()))?;
+    let input = input.filter(|input| input != "-").map(PathBuf::from);
+    if in_place {
+        if input.is_none() {
+            return Err(Error::Usage("--in-place needs an INPUT file".into()));
+        }
+        if output.is_some() {
+            return Err(Error::Usage("--in-place and --output conflict".into()));
+        }
+    }
+    if let (Some(input), Some(output)) = (&input, &output) {
+        if same_file(input, output) {
+            return Err(Error::Usage(
+                "INPUT and --output are the same file; use --in-place".into(),
+            ));
+        }
+    }
+    if salt_file.is_some() && !matches!(key, KeySource::Passphrase) {
+        return Err(Error::Usage("--salt-file needs --passphrase".into()));
+    }
+
+    Ok(Command::Munge(Options {
+        key,
+        input,
+        output,
+        in_place,
+        offset,
+        salt_file,
+        encode,
+        decode,
+    }))
+}
+
+/// Munge everything from `input` into `output`, decoding and encoding as the options ask
+fn munge(
+    xorcism: Xorcism<Vec<u8>>,
+    options: &Options,
+    input: &mut impl Read,
+    output: impl Write,
+) -> io::Result<()> {
+    let mut input: Box<dyn Read + '_> = match options.decode {
+        Some(encoding) => Box::new(DecodeReader::new(encoding, input)),
+        None => Box::new(input),
+    };
+    let output = BufWriter::new(output);
+
+    let Some(encoding) = options.encode else {
+        let mut writer = xorcism.writer(output);
+        io::copy(&mut input, &mut writer)?;
+        return writer.flush();
+    };
+    let mut writer = xorcism.writer(EncodeWriter::new(encoding, output));
+    io::copy(&mut input, &mut writer)?;
+    let (encoder, _) = writer.into_inner();
+    let mut output = encoder.finish()?;
+    // PEM armor ends its own last line
+    if encoding != Encoding::Pem {
+        output.write_all(b"\n")?;
+    }
+    output.flush()
+}
+
+/// Munge `path` into a temporary file next to it, then rename that over the original, so
+/// the original is left untouched if anything goes wrong
+fn munge_in_place(
+    xorcism: Xorcism<Vec<u8>>,
+    options: &Options,
+    path: &Path,
+    new_salt: Option<NewSalt>,
+) -> Result<(), Error> {
+    let mut input = fs::File::open(path).map_err(describe(path))?;
+    let permissions = input.metadata().map_err(describe(path))?.permissions();
+
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{}.xorcism-{}", file_name, std::process::id()));
+    let tmp = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp_path)
+        .map_err(describe(path))?;
+
+    // the salt goes in before the munged data replaces the original, so it can't be lost
+    let result = munge(xorcism, options, &mut input, &tmp)
+        .and_then(|()| tmp.sync_all())
+        .and_then(|()| fs::set_permissions(&tmp_path, permissions))
+        .map_err(describe(path))
+        .and_then(|()| new_salt.map_or(Ok(()), |new_salt| new_salt.write()))
+        .and_then(|()| fs::rename(&tmp_path, path).map_err(describe(path)));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+
+    result
+}
+
+/// Whether `a` and `b` are the same existing file, so creating one would empty the other
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Prefix an I/O error with the path it happened on
+fn describe(path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |err| Error::Run(format!("{}: {}", path.display(), err))
+}
+
+/// The path with `.salt` added to its file name
+fn beside(path: &Path) -> PathBuf {
+    let mut salt = path.as_os_str().to_owned();
+    salt.push(".salt");
+    PathBuf::from(salt)
+}
+
+/// Find where the passphrase's salt is kept, if there is anywhere to keep it
+fn salt_file(options: &Options) -> Option<SaltFile> {
+    if let Some(path) = &options.salt_file {
+        return Some(if path.exists() {
+            SaltFile::Read(path.clone())
+        } else {
+            SaltFile::Create(path.clone())
+        });
+    }
+
+    match options.input.as_deref().map(beside) {
+        Some(salt) if salt.exists() => Some(SaltFile::Read(salt)),
+        Some(salt) if options.in_place => Some(SaltFile::Create(salt)),
+        _ => options.output.as_deref().map(beside).map(SaltFile::Create),
+    }
+}
+
+/// Turn terminal echo on or off with `stty`, returning whether that worked
+#[cfg(unix)]
+fn set_echo(terminal: &fs::File, on: bool) -> bool {
+    let Ok(terminal) = terminal.try_clone() else {
+        return false;
+    };
+    std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(terminal)
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+#[cfg(not(unix))]
+fn set_echo(_terminal: &fs::File, _on: bool) -> bool {
+    false
+}
+
+/// Ask for a line on the terminal, hiding what is typed where the terminal allows it
+fn prompt(message: &str) -> Result<String, Error> {
+    let describe = |err: io::Error| Error::Run(format!("reading passphrase: {}", err));
+    let terminal = fs::File::open(TERMINAL).map_err(describe)?;
+    eprint!("{}", message);
+
+    let hidden = set_echo(&terminal, false);
+    let mut line = String::new();
+    let result = io::BufReader::new(&terminal).read_line(&mut line);
+    if hidden {
+        set_echo(&terminal, true);
+        eprintln!();
+    }
+    result.map_err(describe)?;
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Get the passphrase from the environment or the terminal, asking twice for a new one so a
+/// typo doesn't leave the data munged with an unknown key
+fn read_passphrase(new: bool) -> Result<String, Error> {
+    let passphrase = match env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = prompt("Passphrase: ")?;
+            if new && prompt("Confirm passphrase: ")? != passphrase {
+                return Err(Error::Run("passphrases do not match".into()));
+            }
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Err(Error::Run("passphrase must not be empty".into()));
+    }
+    Ok(passphrase)
+}
+
+/// How many times to stretch a new passphrase, from the environment if it says
+fn iterations() -> Result<u32, Error> {
+    match env::var(ITERATIONS_VAR) {
+        Ok(n) => n
+            .parse()
+            .map_err(|_| Error::Usage(format!("invalid ${}: {:?}", ITERATIONS_VAR, n))),
+        Err(_) => Ok(kdf::DEFAULT_ITERATIONS),
+    }
+}
+
+/// Derive the key from a passphrase and its salt, making a new salt if the data is new
+fn passphrase_key(options: &Options) -> Result<(Vec<u8>, Option<NewSalt>), Error> {
+    let salt_file = salt_file(options).ok_or_else(|| {
+        Error::Usage("--passphrase needs --salt-file unless INPUT or OUTPUT is a file".into())
+    })?;
+
+    let (iterations, salt) = match &salt_file {
+        SaltFile::Read(path) => read_salt(path)?,
+        SaltFile::Create(_) => (iterations()?, kdf::random_salt().to_vec()),
+    };
+    let passphrase = read_passphrase(matches!(salt_file, SaltFile::Create(_)))?;
+    let key = kdf::derive_key(&passphrase, &salt, iterations, PASSPHRASE_KEY_LEN);
+
+    let new_salt = match salt_file {
+        SaltFile::Read(_) => None,
+        SaltFile::Create(path) => Some(NewSalt {
+            path,
+            iterations,
+            salt,
+        }),
+    };
+    Ok((key, new_salt))
+}
+
+fn run(options: Options) -> Result<(), Error> {
+    let (key, new_salt) = options.key.load(&options)?;
+    let mut xorcism = Xorcism::try_with_key(key).map_err(|err| Error::Usage(err.to_string()))?;
+    xorcism.seek_to(options.offset);
+
+    match (&options.input, &options.output) {
+        (Some(input), _) if options.in_place => munge_in_place(xorcism, &options, input, new_salt),
+        (input, output) => {
+            let mut reader: Box<dyn Read> = match input {
+                Some(path) => Box::new(fs::File::open(path).map_err(describe(path))?),
+                None => Box::new(io::stdin().lock()),
+            };
+            let writer: Box<dyn Write> = match output {
+                Some(path) => Box::new(fs::File::create(path).map_err(describe(path))?),
+                None => Box::new(io::stdout().lock()),
+            };
+            if let Some(new_salt) = new_salt {
+                new_salt.write()?;
+            }
+
+            Ok(munge(xorcism, &options, &mut reader, writer)?)
+        }
+    }
+}
+
+/// Show a partly known plaintext, with `_` for unknown bytes and `.` for unprintable ones
+fn render(plaintext: &[Option<u8>]) -> String {
+    plaintext
+        .iter()
+        .map(|byte| match byte {
+            Some(byte) if byte.is_ascii_graphic() || *byte == b' ' => *byte as char,
+            Some(_) => '.',
+            None => '_',
+        })
+        .collect()
+}
+
+/// Run one `crack` command, writing its results to `out`
+fn crack_command(
+    pad: &mut ManyTimePad,
+    model: &ByteFrequencies,
+    line: &str,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+    match command {
+        "drag" => {
+            let matches = pad.drag(rest.as_bytes(), model);
+            if matches.is_empty() {
+                writeln!(out, "no plausible matches")?;
+            }
+            for found in matches.iter().take(DRAG_RESULTS) {
+                writeln!(
+                    out,
+                    "messages {} & {}  offset {:>5}  {:?}",
+                    found.pair.0,
+                    found.pair.1,
+                    found.offset,
+                    String::from_utf8_lossy(&found.revealed)
+                )?;
+            }
+        }
+        "place" => {
+            let mut fields = rest.splitn(3, ' ');
+            let message = fields.next().and_then(|field| field.parse().ok());
+            let offset = fields.next().and_then(|field| field.parse().ok());
+            match (message, offset, fields.next()) {
+                (Some(message), Some(offset), Some(text)) => {
+                    match pad.place(message, offset, text.as_bytes()) {
+                        Ok(()) => writeln!(out, "placed {} bytes", text.len())?,
+                        Err(err) => writeln!(out, "{}", err)?,
+                    }
+                }
+                _ => writeln!(out, "usage: place MSG OFFSET TEXT")?,
+            }
+        }
+        "forget" => {
+            let mut fields = rest.split(' ').map(str::parse);
+            match (fields.next(), fields.next()) {
+                (Some(Ok(offset)), Some(Ok(len))) => pad.forget(offset, len),
+                _ => writeln!(out, "usage: forget OFFSET LEN")?,
+            }
+        }
+        "show" => {
+            for message in 0..pad.ciphertexts().len() {
+                let plaintext = pad.plaintext(message).unwrap_or_default();
+                writeln!(out, "{:>3}: {}", message, render(&plaintext))?;
+            }
+        }
+        "key" => {
+            for byte in pad.keystream() {
+                match byte {
+                    Some(byte) => write!(out, "{:02x}", byte)?,
+                    None => write!(out, "??")?,
+                }
+            }
+            writeln!(out)?;
+        }
+        "help" => writeln!(out, "{}", CRACK_HELP)?,
+        "" => {}
+        _ => writeln!(out, "unknown command {:?}; try 'help'", command)?,
+    }
+
+    Ok(())
+}
+
+/// Interactively crack files munged with the same keystream, reading commands from stdin
+fn crack(options: CrackOptions) -> Result<(), Error> {
+    let model = match &options.corpus {
+        Some(path) => ByteFrequencies::from_corpus_file(path).map_err(describe(path))?,
+        None => ByteFrequencies::english(),
+    };
+    let paths = &options.paths;
+    let ciphertexts = paths
+        .iter()
+        .map(|path| fs::read(path).map_err(describe(path)))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (message, path) in paths.iter().enumerate() {
+        println!("{:>3}: {}", message, path.display());
+    }
+
+    let mut pad = ManyTimePad::new(ciphertexts);
+    let mut out = io::stdout().lock();
+    writeln!(out, "type 'help' for commands")?;
+    write!(out, "> ")?;
+    out.flush()?;
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        if matches!(line.as_str(), "quit" | "exit") {
+            break;
+        }
+
+        crack_command(&mut pad, &model, &line, &mut out)?;
+        write!(out, "> ")?;
+        out.flush()?;
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let result = parse_args(env::args().skip(1)).and_then(|command| match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Command::Munge(options) => run(options),
+        Command::Crack(options) => crack(options),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err @ Error::Usage(_)) => {
+            eprintln!(
+                "xorcism: {}\nTry 'xorcism --help' for more information.",
+                err
+            );
+            ExitCode::from(2)
+        }
+        Err(err @ Error::Run(_)) => {
+            eprintln!("xorcism: {}", err);
+            ExitCode::FAILURE
+        }
+    }
 }
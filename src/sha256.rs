@@ -0,0 +1,197 @@
+//! SHA-256 and HMAC-SHA-256, as specified in FIPS 180-4 and RFC 2104.
+//!
+//! These are plain, portable implementations for deriving and checking keys, with no
+//! attempt at constant-time behaviour beyond what the algorithms give for free.
+
+/// The length of a SHA-256 digest in bytes
+pub const DIGEST_LEN: usize = 32;
+
+/// The size of the blocks SHA-256 compresses, in bytes
+const BLOCK_LEN: usize = 64;
+
+/// The first 32 bits of the fractional parts of the square roots of the first 8 primes
+const INITIAL_STATE: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// The first 32 bits of the fractional parts of the cube roots of the first 64 primes
+const ROUND_CONSTANTS: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// An incremental SHA-256 hash
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    block: [u8; BLOCK_LEN],
+    block_len: usize, // bytes waiting in `block`
+    len: u64,         // bytes hashed so far
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+impl Sha256 {
+    /// Start a new hash
+    pub fn new() -> Sha256 {
+        Sha256 {
+            state: INITIAL_STATE,
+            block: [0; BLOCK_LEN],
+            block_len: 0,
+            len: 0,
+        }
+    }
+
+    /// Hash `data` after everything hashed so far
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+
+        if self.block_len > 0 {
+            let take = data.len().min(BLOCK_LEN - self.block_len);
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+            if self.block_len < BLOCK_LEN {
+                return;
+            }
+            let block = self.block;
+            compress(&mut self.state, &block);
+            self.block_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(BLOCK_LEN);
+        for block in &mut blocks {
+            compress(&mut self.state, block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.block[..rest.len()].copy_from_slice(rest);
+        self.block_len = rest.len();
+    }
+
+    /// The digest of everything hashed
+    pub fn finalize(mut self) -> [u8; DIGEST_LEN] {
+        let bits = self.len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != BLOCK_LEN - 8 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+
+        let mut digest = [0; DIGEST_LEN];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(self.state) {
+            bytes.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+/// Mix one block into the hash state
+fn compress(state: &mut [u32; 8], block: &[u8; BLOCK_LEN]) {
+    let mut schedule = [0_u32; 64];
+    for (word, bytes) in schedule.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..64 {
+        let s0 = schedule[i - 15].rotate_right(7)
+            ^ schedule[i - 15].rotate_right(18)
+            ^ (schedule[i - 15] >> 3);
+        let s1 = schedule[i - 2].rotate_right(17)
+            ^ schedule[i - 2].rotate_right(19)
+            ^ (schedule[i - 2] >> 10);
+        schedule[i] = schedule[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(schedule[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for (round_constant, word) in ROUND_CONSTANTS.iter().zip(schedule) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let choice = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(choice)
+            .wrapping_add(*round_constant)
+            .wrapping_add(word);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let majority = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(majority);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (word, add) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *word = word.wrapping_add(add);
+    }
+}
+
+/// The SHA-256 digest of `data`
+pub fn sha256(data: &[u8]) -> [u8; DIGEST_LEN] {
+    let mut hash = Sha256::new();
+    hash.update(data);
+    hash.finalize()
+}
+
+/// An incremental HMAC-SHA-256
+///
+/// Cloning a keyed HMAC before updating it saves hashing the key again, which is what makes
+/// iterated key derivation affordable.
+#[derive(Debug, Clone)]
+pub struct HmacSha256 {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl HmacSha256 {
+    /// Start a new HMAC keyed with `key`
+    pub fn new(key: &[u8]) -> HmacSha256 {
+        let mut block = [0; BLOCK_LEN];
+        if key.len() > BLOCK_LEN {
+            block[..DIGEST_LEN].copy_from_slice(&sha256(key));
+        } else {
+            block[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = Sha256::new();
+        inner.update(&block.map(|byte| byte ^ 0x36));
+        let mut outer = Sha256::new();
+        outer.update(&block.map(|byte| byte ^ 0x5c));
+        HmacSha256 { inner, outer }
+    }
+
+    /// Authenticate `data` after everything authenticated so far
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// The tag of everything authenticated
+    pub fn finalize(self) -> [u8; DIGEST_LEN] {
+        let mut outer = self.outer;
+        outer.update(&self.inner.finalize());
+        outer.finalize()
+    }
+}
+
+/// The HMAC-SHA-256 tag of `data` under `key`
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> [u8; DIGEST_LEN] {
+    let mut hmac = HmacSha256::new(key);
+    hmac.update(data);
+    hmac.finalize()
+}
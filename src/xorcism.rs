@@ -1,56 +1,269 @@
-use std::{
-    borrow::Borrow,
-    io::{Read, Write},
-};
+#[cfg(any(feature = "futures-io", feature = "tokio"))]
+use crate::async_io::{AsyncXorDataReader, AsyncXorDataWriter};
+use crate::checkpoint::{Checkpoint, CheckpointError};
+use crate::keystream::{xor_words, Keystream};
+use core::{borrow::Borrow, fmt, iter::FusedIterator};
+#[cfg(feature = "io")]
+use std::io::{Read, Seek, SeekFrom, Write};
+#[cfg(feature = "std")]
+use std::thread;
+
+/// Reasons a key can be rejected by [`Xorcism::try_new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum XorcismError {
+    /// The key has no bytes to XOR with
+    EmptyKey,
+}
+
+impl fmt::Display for XorcismError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            XorcismError::EmptyKey => write!(f, "key must not be empty"),
+        }
+    }
+}
+
+impl core::error::Error for XorcismError {}
+
+/// The length of a state saved by [`Xorcism::save_state`], in bytes
+pub const STATE_LEN: usize = 8;
+
+/// Keys shorter than this are expanded into a repeating pattern by `munge_in_place`,
+/// so that the word-at-a-time XOR gets long runs to work on.
+const SHORT_KEY_LEN: usize = 256;
+
+/// Size of the stack buffer short keys are expanded into.
+const PATTERN_LEN: usize = 512;
+
+/// The least data worth handing to a thread of its own by `munge_in_place_parallel`; below
+/// this, starting the thread costs more than it saves.
+#[cfg(feature = "std")]
+const MIN_PARALLEL_CHUNK: usize = 64 * 1024;
 
 /// A munger which XORs a key with some data
+///
+/// The key can be stored however suits the caller: [`Xorcism::new`] borrows it, while
+/// [`Xorcism::with_key`] takes ownership of anything which can be viewed as bytes, such as a
+/// `Vec<u8>`, `Arc<[u8]>` or `Cow<[u8]>`. An owned key makes the munger `'static`, so it
+/// can be kept in long-lived structs or moved to other threads.
 #[derive(Clone)]
-pub struct Xorcism<'a> {
+pub struct Xorcism<Key> {
     idx: usize, // next idx to access key
-    key: &'a [u8],
+    pos: u64,   // bytes munged since the start of the keystream
+    key: Key,
 }
 
-impl<'a> Xorcism<'a> {
+impl<'a> Xorcism<&'a [u8]> {
     /// Create a new Xorcism munger from a key
     ///
     /// Should accept anything which has a cheap conversion to a byte slice.
-    pub fn new<Key>(key: &'a Key) -> Xorcism<'a>
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty. Use [`Xorcism::try_new`] for keys which are not known
+    /// to be valid, e.g. those read from configuration.
+    pub fn new<Key>(key: &'a Key) -> Xorcism<&'a [u8]>
     where
         Key: AsRef<[u8]> + ?Sized,
     {
-        Self {
-            idx: 0,
-            key: key.as_ref(),
+        match Self::try_new(key) {
+            Ok(xorcism) => xorcism,
+            Err(err) => panic!("invalid Xorcism key: {}", err),
+        }
+    }
+
+    /// Create a new Xorcism munger from a key, rejecting keys it cannot munge with
+    pub fn try_new<Key>(key: &'a Key) -> Result<Xorcism<&'a [u8]>, XorcismError>
+    where
+        Key: AsRef<[u8]> + ?Sized,
+    {
+        Xorcism::try_with_key(key.as_ref())
+    }
+}
+
+impl<Key> Xorcism<Key>
+where
+    Key: AsRef<[u8]>,
+{
+    /// Create a new Xorcism munger which stores its key by value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is empty. Use [`Xorcism::try_with_key`] for keys which are not
+    /// known to be valid.
+    pub fn with_key(key: Key) -> Xorcism<Key> {
+        match Self::try_with_key(key) {
+            Ok(xorcism) => xorcism,
+            Err(err) => panic!("invalid Xorcism key: {}", err),
         }
     }
 
+    /// Create a new Xorcism munger which stores its key by value, rejecting keys it cannot
+    /// munge with
+    pub fn try_with_key(key: Key) -> Result<Xorcism<Key>, XorcismError> {
+        if key.as_ref().is_empty() {
+            return Err(XorcismError::EmptyKey);
+        }
+
+        Ok(Self {
+            idx: 0,
+            pos: 0,
+            key,
+        })
+    }
+
+    /// The key this munger XORs with
+    pub fn key(&self) -> &[u8] {
+        self.key.as_ref()
+    }
+
     /// XOR each byte of the input buffer with a byte from the key.
     ///
     /// Note that this is stateful: repeated calls are likely to produce different results,
     /// even with identical inputs.
     pub fn munge_in_place(&mut self, data: &mut [u8]) {
-        for byte in data.iter_mut() {
-            self.xor_inplace(byte);
+        let key = self.key.as_ref();
+
+        if key.len() < SHORT_KEY_LEN {
+            // a short key is rotated to start at idx and repeated as many whole times as fit
+            // in the pattern; after each pattern's worth of data the key lines up again
+            let period = PATTERN_LEN / key.len() * key.len();
+            let mut pattern = [0; PATTERN_LEN];
+            let pattern = &mut pattern[..period.min(data.len())];
+            for (byte, key_byte) in pattern.iter_mut().zip(key.iter().cycle().skip(self.idx)) {
+                *byte = *key_byte;
+            }
+
+            for chunk in data.chunks_mut(period) {
+                xor_words(chunk, &pattern[..chunk.len()]);
+            }
+        } else {
+            // a long key is already a long enough run to XOR against directly
+            let mut idx = self.idx;
+            let mut rest = &mut data[..];
+            while !rest.is_empty() {
+                let len = rest.len().min(key.len() - idx);
+                let (chunk, tail) = rest.split_at_mut(len);
+                xor_words(chunk, &key[idx..idx + len]);
+                idx = 0;
+                rest = tail;
+            }
         }
+
+        self.advance_by(data.len());
+    }
+
+    /// XOR each byte of `src` with a byte from the key, writing the result to `dst`.
+    ///
+    /// This is [`Xorcism::munge_in_place`] for data which must be left untouched, such as a
+    /// buffer in flash or one still owned by a driver.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` and `dst` have different lengths.
+    pub fn munge_into(&mut self, src: &[u8], dst: &mut [u8]) {
+        dst.copy_from_slice(src);
+        self.munge_in_place(dst);
+    }
+
+    /// XOR each byte of the input buffer with a byte from the key, on up to `threads`
+    /// threads at once.
+    ///
+    /// The key index of each byte follows from its offset alone, so the buffer is split into
+    /// chunks which are munged independently. The result, and the state left behind, are
+    /// exactly those of [`Xorcism::munge_in_place`]. Buffers too small to be worth splitting
+    /// `threads` ways are split fewer ways, or munged on the calling thread.
+    #[cfg(feature = "std")]
+    pub fn munge_in_place_parallel(&mut self, data: &mut [u8], threads: usize) {
+        let threads = threads.min(data.len().div_ceil(MIN_PARALLEL_CHUNK));
+        if threads <= 1 {
+            return self.munge_in_place(data);
+        }
+
+        let chunk_len = data.len().div_ceil(threads);
+        let key = self.key();
+        thread::scope(|scope| {
+            let mut chunks = data.chunks_mut(chunk_len).enumerate();
+            let (_, first) = chunks.next().expect("data is not empty");
+            for (i, chunk) in chunks {
+                let mut xs = Xorcism::with_key(key);
+                xs.seek_to(self.pos + (i * chunk_len) as u64);
+                scope.spawn(move || xs.munge_in_place(chunk));
+            }
+
+            // the calling thread takes the first chunk rather than waiting idle
+            let mut xs = Xorcism::with_key(key);
+            xs.seek_to(self.pos);
+            xs.munge_in_place(first);
+        });
+
+        self.advance_by(data.len());
+    }
+
+    /// Move to `offset` bytes from the start of the keystream.
+    ///
+    /// Munging continues exactly as if `offset` bytes had been munged since [`Xorcism::new`],
+    /// so any range of a munged stream can be processed without touching the bytes before it.
+    pub fn seek_to(&mut self, offset: u64) {
+        self.idx = (offset % self.key().len() as u64) as usize;
+        self.pos = offset;
+    }
+
+    /// The offset into the keystream of the next byte to be munged
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    /// Save how far the munger has got, to carry on later with [`Xorcism::restore_state`]
+    ///
+    /// Only the position is saved, never the key, so the state can be kept anywhere a few
+    /// bytes fit, such as a register which survives a reset.
+    pub fn save_state(&self) -> [u8; STATE_LEN] {
+        self.pos.to_be_bytes()
+    }
+
+    /// Carry on from a state saved by [`Xorcism::save_state`]
+    ///
+    /// The munger must have the same key as the one which saved the state.
+    pub fn restore_state(&mut self, state: &[u8; STATE_LEN]) {
+        self.seek_to(u64::from_be_bytes(*state));
+    }
+
+    /// Record how far the munger has got, to carry on later with [`Xorcism::resume`]
+    ///
+    /// Unlike [`Xorcism::save_state`], the checkpoint also records a fingerprint of the key,
+    /// so resuming with the wrong key fails instead of munging garbage.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::new(self.key(), self.pos)
+    }
+
+    /// Create a munger with `key` which carries on from `checkpoint`
+    ///
+    /// Fails with [`CheckpointError::WrongKey`] unless `key` is the key the checkpoint was
+    /// made with.
+    pub fn resume(key: Key, checkpoint: &Checkpoint) -> Result<Xorcism<Key>, CheckpointError> {
+        if !checkpoint.matches(key.as_ref()) {
+            return Err(CheckpointError::WrongKey);
+        }
+
+        let mut xorcism = Xorcism::try_with_key(key).map_err(|_| CheckpointError::WrongKey)?;
+        xorcism.seek_to(checkpoint.position);
+        Ok(xorcism)
     }
 
     fn advance(&mut self) {
         self.idx += 1;
+        self.pos += 1;
 
-        if self.idx >= self.key.len() {
+        if self.idx >= self.key().len() {
             self.idx = 0;
         }
     }
 
-    fn xor_inplace(&mut self, byte: &mut u8) {
-        *byte ^= self.key[self.idx];
-        self.advance();
-    }
-
-    fn xor(&mut self, byte: &u8) -> u8 {
-        let ret = *byte ^ self.key[self.idx];
-        self.advance();
-        ret
+    fn advance_by(&mut self, n: usize) {
+        self.idx = (self.idx + n % self.key().len()) % self.key().len();
+        self.pos += n as u64;
     }
 
     /// XOR each byte of the data with a byte from the key.
@@ -60,85 +273,336 @@ impl<'a> Xorcism<'a> {
     ///
     /// Should accept anything which has a cheap conversion to a byte iterator.
     /// Shouldn't matter whether the byte iterator's values are owned or borrowed.
-    pub fn munge<Data, T>(&mut self, data: Data) -> impl Iterator<Item = u8>
+    ///
+    /// The returned iterator is lazy: each byte is munged as it is pulled, and the key
+    /// only advances past the bytes which have actually been yielded.
+    pub fn munge<Data, T>(&mut self, data: Data) -> Munge<'_, Self, Data::IntoIter>
     where
         Data: IntoIterator<Item = T>,
         T: Borrow<u8>,
     {
-        let data: Vec<u8> = data
-            .into_iter()
-            .map(|byte| self.xor(byte.borrow()))
-            .collect();
+        Munge::new(self, data.into_iter())
+    }
 
-        XorData { data, cur_idx: 0 }
+    /// Wrap a reader so that everything read through it is munged.
+    #[cfg(feature = "io")]
+    pub fn reader<DataReader>(self, reader: DataReader) -> XorDataReader<Self, DataReader>
+    where
+        DataReader: Read,
+    {
+        XorDataReader::new(self, reader)
     }
 
-    pub fn reader(self, reader: impl Read + 'a) -> impl Read + 'a {
-        XorDataReader {
-            xor: self,
-            data: reader,
-        }
+    /// Wrap a writer so that everything written through it is munged.
+    #[cfg(feature = "io")]
+    pub fn writer<DataWriter>(self, writer: DataWriter) -> XorDataWriter<Self, DataWriter>
+    where
+        DataWriter: Write,
+    {
+        XorDataWriter::new(self, writer)
     }
 
-    pub fn writer(self, writer: impl Write + 'a) -> impl Write + 'a {
-        XorDataWriter {
-            xor: self,
-            data: writer,
-        }
+    /// Wrap an async reader so that everything read through it is munged.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    pub fn async_reader<DataReader>(
+        self,
+        reader: DataReader,
+    ) -> AsyncXorDataReader<Self, DataReader> {
+        AsyncXorDataReader::new(self, reader)
+    }
+
+    /// Wrap an async writer so that everything written through it is munged.
+    #[cfg(any(feature = "futures-io", feature = "tokio"))]
+    pub fn async_writer<DataWriter>(
+        self,
+        writer: DataWriter,
+    ) -> AsyncXorDataWriter<Self, DataWriter> {
+        AsyncXorDataWriter::new(self, writer)
+    }
+}
+
+/// The repeating key as a keystream, so it can be used wherever any [`Keystream`] will do
+impl<Key> Keystream for Xorcism<Key>
+where
+    Key: AsRef<[u8]>,
+{
+    fn next_byte(&mut self) -> u8 {
+        let byte = self.key()[self.idx];
+        self.advance();
+        byte
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        Xorcism::seek_to(self, offset)
+    }
+
+    fn position(&self) -> u64 {
+        Xorcism::position(self)
+    }
+
+    fn fill(&mut self, buf: &mut [u8]) {
+        buf.fill(0);
+        Xorcism::munge_in_place(self, buf)
+    }
+
+    fn munge_in_place(&mut self, data: &mut [u8]) {
+        Xorcism::munge_in_place(self, data)
     }
 }
 
-struct XorData {
-    data: Vec<u8>,
-    cur_idx: usize,
+/// A lazy iterator which munges each byte of the data as it is pulled.
+///
+/// Created by [`Xorcism::munge`] or [`Keystream::munge`].
+pub struct Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+{
+    xor: &'x mut Stream,
+    data: Data,
+    back: usize, // bytes yielded from the back, whose keystream bytes are skipped over on drop
 }
 
-impl Iterator for XorData {
+impl<'x, Stream, Data> Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+{
+    pub(crate) fn new(xor: &'x mut Stream, data: Data) -> Self {
+        Munge { xor, data, back: 0 }
+    }
+}
+
+impl<'x, Stream, Data, T> Iterator for Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+    Data: Iterator<Item = T>,
+    T: Borrow<u8>,
+{
     type Item = u8;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.cur_idx < self.data.len() {
-            let ret = self.data[self.cur_idx];
-            self.cur_idx += 1;
+        let byte = self.data.next()?;
+        Some(byte.borrow() ^ self.xor.next_byte())
+    }
 
-            Some(ret)
-        } else {
-            None
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.data.size_hint()
+    }
+}
+
+/// Yielding from the back needs to know how far the byte is from the front, which is
+/// why the data must also have an exact size. Only a repeating key can look that far
+/// ahead cheaply, so other keystreams only munge forwards.
+impl<'x, Key, Data, T> DoubleEndedIterator for Munge<'x, Xorcism<Key>, Data>
+where
+    Key: AsRef<[u8]>,
+    Data: DoubleEndedIterator<Item = T> + ExactSizeIterator,
+    T: Borrow<u8>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let byte = self.data.next_back()?;
+        let key = self.xor.key();
+        let idx = (self.xor.idx + self.data.len() % key.len()) % key.len();
+        self.back += 1;
+
+        Some(byte.borrow() ^ key[idx])
+    }
+}
+
+impl<'x, Stream, Data, T> ExactSizeIterator for Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+    Data: ExactSizeIterator<Item = T>,
+    T: Borrow<u8>,
+{
+}
+
+impl<'x, Stream, Data, T> FusedIterator for Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+    Data: FusedIterator<Item = T>,
+    T: Borrow<u8>,
+{
+}
+
+impl<'x, Stream, Data> Drop for Munge<'x, Stream, Data>
+where
+    Stream: Keystream + ?Sized,
+{
+    fn drop(&mut self) {
+        if self.back > 0 {
+            let pos = self.xor.position();
+            self.xor.seek_to(pos + self.back as u64);
         }
     }
 }
 
-struct XorDataReader<'a, DataReader> {
-    xor: Xorcism<'a>,
+/// A reader which munges everything read from the wrapped reader.
+///
+/// Created by [`Xorcism::reader`] or [`Keystream::reader`].
+#[cfg(feature = "io")]
+pub struct XorDataReader<Stream, DataReader> {
+    xor: Stream,
     data: DataReader,
 }
-impl<'a, DataReader> Read for XorDataReader<'a, DataReader>
+
+#[cfg(feature = "io")]
+impl<Stream, DataReader> XorDataReader<Stream, DataReader> {
+    pub(crate) fn new(xor: Stream, data: DataReader) -> Self {
+        XorDataReader { xor, data }
+    }
+
+    /// Get a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &DataReader {
+        &self.data
+    }
+
+    /// Get a mutable reference to the wrapped reader.
+    ///
+    /// Reading from it directly bypasses the munger, so the key position no longer
+    /// matches the stream position.
+    pub fn get_mut(&mut self) -> &mut DataReader {
+        &mut self.data
+    }
+
+    /// Unwrap this reader, returning the wrapped reader and the munger at its current key position.
+    pub fn into_inner(self) -> (DataReader, Stream) {
+        (self.data, self.xor)
+    }
+}
+
+#[cfg(feature = "io")]
+impl<Stream, DataReader> Read for XorDataReader<Stream, DataReader>
 where
+    Stream: Keystream,
     DataReader: Read,
 {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let i = self.data.read(buf)?;
-        self.xor.munge_in_place(buf);
+        self.xor.munge_in_place(&mut buf[..i]);
 
         Ok(i)
     }
 }
 
-struct XorDataWriter<'a, DataWriter> {
-    xor: Xorcism<'a>,
+/// Seeking moves the key along with the wrapped reader: offset `n` of the wrapped reader
+/// is always munged with keystream position `n`, so the reader should start at the
+/// beginning of the munged data.
+#[cfg(feature = "io")]
+impl<Stream, DataReader> Seek for XorDataReader<Stream, DataReader>
+where
+    Stream: Keystream,
+    DataReader: Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let offset = self.data.seek(pos)?;
+        self.xor.seek_to(offset);
+
+        Ok(offset)
+    }
+}
+
+/// A writer which munges everything written to it before passing it on to the wrapped writer.
+///
+/// Created by [`Xorcism::writer`] or [`Keystream::writer`].
+#[cfg(feature = "io")]
+pub struct XorDataWriter<Stream, DataWriter> {
+    xor: Stream,
     data: DataWriter,
+    /// keystream taken from `xor` for bytes the wrapped writer hasn't accepted yet, so a
+    /// short write doesn't have to rewind the keystream
+    keystream: Vec<u8>,
+    buf: Vec<u8>, // scratch space for munged output, reused between writes
 }
 
-impl<'a, DataWriter> Write for XorDataWriter<'a, DataWriter>
+#[cfg(feature = "io")]
+impl<Stream, DataWriter> XorDataWriter<Stream, DataWriter> {
+    pub(crate) fn new(xor: Stream, data: DataWriter) -> Self {
+        XorDataWriter {
+            xor,
+            data,
+            keystream: Vec::new(),
+            buf: Vec::new(),
+        }
+    }
+
+    /// Get a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &DataWriter {
+        &self.data
+    }
+
+    /// Get a mutable reference to the wrapped writer.
+    ///
+    /// Writing to it directly bypasses the munger, so the key position no longer
+    /// matches the stream position.
+    pub fn get_mut(&mut self) -> &mut DataWriter {
+        &mut self.data
+    }
+}
+
+#[cfg(feature = "io")]
+impl<Stream, DataWriter> XorDataWriter<Stream, DataWriter>
 where
+    Stream: Keystream,
+{
+    /// Unwrap this writer, returning the wrapped writer and the munger at its current key position.
+    ///
+    /// The wrapped writer is not flushed.
+    pub fn into_inner(mut self) -> (DataWriter, Stream) {
+        if !self.keystream.is_empty() {
+            let position = self.xor.position() - self.keystream.len() as u64;
+            self.xor.seek_to(position);
+        }
+
+        (self.data, self.xor)
+    }
+}
+
+#[cfg(feature = "io")]
+impl<Stream, DataWriter> Write for XorDataWriter<Stream, DataWriter>
+where
+    Stream: Keystream,
     DataWriter: Write,
 {
+    /// The key only advances past the bytes the inner writer accepted, so a caller
+    /// resending the rest after a short write gets them munged with the right key bytes.
+    /// The keystream for the rest is kept rather than rewound, so keystreams which are slow
+    /// to seek cost nothing extra however the wrapped writer splits up the data.
     fn write(&mut self, input: &[u8]) -> std::io::Result<usize> {
-        let buf = self.xor.munge(input).collect::<Vec<_>>();
-        self.data.write(&buf)
+        let have = self.keystream.len();
+        if have < input.len() {
+            self.keystream.resize(input.len(), 0);
+            self.xor.fill(&mut self.keystream[have..]);
+        }
+
+        self.buf.clear();
+        self.buf.extend_from_slice(input);
+        xor_words(&mut self.buf, &self.keystream[..input.len()]);
+
+        let i = self.data.write(&self.buf)?;
+        self.keystream.drain(..i);
+
+        Ok(i)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        Ok(())
+        self.data.flush()
+    }
+}
+
+/// Seeking moves the key along with the wrapped writer: offset `n` of the wrapped writer
+/// is always munged with keystream position `n`, so the writer should start at the
+/// beginning of the munged data.
+#[cfg(feature = "io")]
+impl<Stream, DataWriter> Seek for XorDataWriter<Stream, DataWriter>
+where
+    Stream: Keystream,
+    DataWriter: Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let offset = self.data.seek(pos)?;
+        self.keystream.clear();
+        self.xor.seek_to(offset);
+
+        Ok(offset)
     }
 }
@@ -0,0 +1,136 @@
+#![cfg(feature = "std")]
+
+use exercism::analysis::{
+    break_repeating_key, estimate_key_lengths, hamming_distance, index_of_coincidence, recover_key,
+    ByteFrequencies,
+};
+use exercism::xorcism::Xorcism;
+
+const ENGLISH: &[u8] = include_bytes!("data/english.txt");
+
+/// a key of `len` bytes with no obvious structure
+fn key(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 73 + 41) as u8 ^ 0x5a).collect()
+}
+
+#[test]
+fn hamming_distance_counts_differing_bits() {
+    assert_eq!(hamming_distance(b"this is a test", b"wokka wokka!!!"), 37);
+    assert_eq!(hamming_distance(&[0xff], &[0x00]), 8);
+    assert_eq!(hamming_distance(b"same", b"same"), 0);
+    // only the common prefix is compared
+    assert_eq!(hamming_distance(&[1, 2, 3], &[1]), 0);
+}
+#[test]
+fn index_of_coincidence_of_small_inputs() {
+    assert_eq!(index_of_coincidence([]), 0.0);
+    assert_eq!(index_of_coincidence([7]), 0.0);
+    assert_eq!(index_of_coincidence([7, 7, 7]), 1.0);
+    assert_eq!(index_of_coincidence([1, 2, 3, 4]), 0.0);
+    assert_eq!(index_of_coincidence([1, 1, 2, 2]), 2.0 * 2.0 / 12.0);
+}
+#[test]
+fn finds_key_length() {
+    for len in 1..=40 {
+        let ciphertext: Vec<u8> = Xorcism::new(&key(len)).munge(ENGLISH).collect();
+        let candidates = estimate_key_lengths(&ciphertext, 40);
+        assert_eq!(candidates[0].len, len);
+        assert!(candidates[0].coincidence > 5.0);
+        assert!(candidates[0].distance < 0.45);
+    }
+}
+#[test]
+fn finds_key_length_from_short_ciphertext() {
+    for len in 1..=40 {
+        let ciphertext: Vec<u8> = Xorcism::new(&key(len)).munge(&ENGLISH[..500]).collect();
+        assert_eq!(estimate_key_lengths(&ciphertext, 40)[0].len, len);
+    }
+}
+#[test]
+fn ranks_every_length_once() {
+    let ciphertext: Vec<u8> = Xorcism::new(&key(7)).munge(ENGLISH).collect();
+    let mut lengths: Vec<usize> = estimate_key_lengths(&ciphertext, 30)
+        .iter()
+        .map(|candidate| candidate.len)
+        .collect();
+    lengths.sort_unstable();
+    assert_eq!(lengths, (1..=30).collect::<Vec<_>>());
+}
+#[test]
+fn skips_lengths_without_two_blocks() {
+    let ciphertext: Vec<u8> = Xorcism::new(&key(3)).munge(&ENGLISH[..20]).collect();
+    let candidates = estimate_key_lengths(&ciphertext, 40);
+    assert_eq!(candidates.len(), 10);
+    assert!(estimate_key_lengths(&[1], 40).is_empty());
+    assert!(estimate_key_lengths(&[], 40).is_empty());
+}
+#[test]
+fn recovers_key() {
+    let english = ByteFrequencies::english();
+    for len in 1..=40 {
+        let key = key(len);
+        let ciphertext: Vec<u8> = Xorcism::new(&key).munge(ENGLISH).collect();
+        let recovered = recover_key(&ciphertext, len, &english).unwrap();
+        assert_eq!(recovered.key, key, "key length {}", len);
+        assert_eq!(recovered.plaintext, ENGLISH);
+        assert!(recovered.confidence > 0.99, "key length {}", len);
+    }
+}
+#[test]
+fn recovers_key_with_a_trained_model() {
+    // train on the first half, break the second
+    let (training, text) = ENGLISH.split_at(ENGLISH.len() / 2);
+    let model = ByteFrequencies::from_sample(training);
+    let key = key(13);
+    let ciphertext: Vec<u8> = Xorcism::new(&key).munge(text).collect();
+    let recovered = recover_key(&ciphertext, 13, &model).unwrap();
+    assert_eq!(recovered.key, key);
+    assert_eq!(recovered.plaintext, text);
+}
+#[test]
+fn unsure_of_key_for_random_data() {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let noise: Vec<u8> = (0..2000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let recovered = recover_key(&noise, 8, &ByteFrequencies::english()).unwrap();
+    assert!(recovered.confidence < 0.5);
+}
+#[test]
+fn recover_key_rejects_bad_lengths() {
+    let english = ByteFrequencies::english();
+    assert_eq!(recover_key(b"abc", 0, &english), None);
+    assert_eq!(recover_key(b"abc", 4, &english), None);
+}
+#[test]
+fn breaks_key_of_unknown_length() {
+    let english = ByteFrequencies::english();
+    for len in [1, 3, 8, 17, 29] {
+        let key = key(len);
+        let ciphertext: Vec<u8> = Xorcism::new(&key).munge(ENGLISH).collect();
+        let recovered = break_repeating_key(&ciphertext, 40, &english).unwrap();
+        assert_eq!(recovered.key, key, "key length {}", len);
+        assert_eq!(recovered.plaintext, ENGLISH);
+    }
+}
+#[test]
+fn english_model_prefers_english() {
+    let english = ByteFrequencies::english();
+    let total: f64 = (0..=u8::MAX).map(|byte| english.probability(byte)).sum();
+    assert!((total - 1.0).abs() < 1e-9);
+    assert!(english.probability(b'e') > english.probability(b'z'));
+    assert!(english.probability(b' ') > english.probability(0));
+    assert!(
+        english.log_likelihood(b"the quick brown fox".iter().copied())
+            > english.log_likelihood(
+                b"\x00\x9f\x13\xfe\x80 \x01\x7f\xc0\x02\x11\xee\x04\xa5\xb7\x08\x99\x10\x1b"
+                    .iter()
+                    .copied()
+            )
+    );
+}
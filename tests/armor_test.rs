@@ -0,0 +1,225 @@
+#![cfg(feature = "io")]
+
+use exercism::armor::{decode, encode, ArmorError, DecodeReader, EncodeWriter, Encoding, LINE_LEN};
+use exercism::xorcism::Xorcism;
+use std::io::{self, Read, Write};
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+const RFC_4648: [(&str, &str, &str); 7] = [
+    ("", "", ""),
+    ("f", "Zg==", "66"),
+    ("fo", "Zm8=", "666f"),
+    ("foo", "Zm9v", "666f6f"),
+    ("foob", "Zm9vYg==", "666f6f62"),
+    ("fooba", "Zm9vYmE=", "666f6f6261"),
+    ("foobar", "Zm9vYmFy", "666f6f626172"),
+];
+
+/// a reader which hands out at most `chunk` bytes per call, like a pipe or socket would
+struct ShortReader<R> {
+    inner: R,
+    chunk: usize,
+}
+impl<R: Read> Read for ShortReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+fn all_bytes() -> Vec<u8> {
+    (0..=255).chain((0..=255).rev()).collect()
+}
+
+#[test]
+fn rfc_4648_vectors() {
+    for (data, base64, hex) in RFC_4648 {
+        assert_eq!(encode(Encoding::Base64, data.as_bytes()), base64);
+        assert_eq!(
+            encode(Encoding::Base64Url, data.as_bytes()),
+            base64.trim_end_matches('=')
+        );
+        assert_eq!(encode(Encoding::Hex, data.as_bytes()), hex);
+        assert_eq!(decode(Encoding::Base64, base64).unwrap(), data.as_bytes());
+        assert_eq!(decode(Encoding::Hex, hex).unwrap(), data.as_bytes());
+    }
+}
+#[test]
+fn url_safe_alphabet() {
+    assert_eq!(encode(Encoding::Base64, &[0xfb, 0xff]), "+/8=");
+    assert_eq!(encode(Encoding::Base64Url, &[0xfb, 0xff]), "-_8");
+    assert_eq!(decode(Encoding::Base64Url, "-_8").unwrap(), [0xfb, 0xff]);
+    assert_eq!(decode(Encoding::Base64Url, "-_8=").unwrap(), [0xfb, 0xff]);
+    assert_eq!(
+        decode(Encoding::Base64Url, "+/8="),
+        Err(ArmorError::InvalidCharacter(b'+'))
+    );
+    assert_eq!(
+        decode(Encoding::Base64, "-_8="),
+        Err(ArmorError::InvalidCharacter(b'-'))
+    );
+}
+#[test]
+fn roundtrip_every_encoding() {
+    let data = all_bytes();
+    for encoding in [
+        Encoding::Hex,
+        Encoding::Base64,
+        Encoding::Base64Url,
+        Encoding::Pem,
+    ] {
+        for len in 0..10 {
+            let text = encode(encoding, &data[..len]);
+            assert_eq!(
+                decode(encoding, &text).unwrap(),
+                &data[..len],
+                "{}",
+                encoding
+            );
+        }
+        let text = encode(encoding, &data);
+        assert_eq!(decode(encoding, &text).unwrap(), data, "{}", encoding);
+    }
+}
+#[test]
+fn hex_is_case_insensitive() {
+    assert_eq!(
+        decode(Encoding::Hex, "DEADbeef").unwrap(),
+        [0xde, 0xad, 0xbe, 0xef]
+    );
+}
+#[test]
+fn whitespace_is_skipped() {
+    assert_eq!(decode(Encoding::Hex, " 66 6f\n6f\t").unwrap(), b"foo");
+    assert_eq!(
+        decode(Encoding::Base64, "Zm9v\r\nYmFy\n").unwrap(),
+        b"foobar"
+    );
+}
+#[test]
+fn pem_layout() {
+    let data = all_bytes();
+    let text = encode(Encoding::Pem, &data);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "-----BEGIN XORCISM DATA-----");
+    assert_eq!(lines[lines.len() - 1], "-----END XORCISM DATA-----");
+    let body = &lines[1..lines.len() - 1];
+    assert!(body[..body.len() - 1]
+        .iter()
+        .all(|line| line.len() == LINE_LEN));
+    assert_eq!(body.concat(), encode(Encoding::Base64, &data));
+    assert!(text.ends_with("-----\n"));
+
+    assert_eq!(
+        encode(Encoding::Pem, b""),
+        "-----BEGIN XORCISM DATA-----\n-----END XORCISM DATA-----\n"
+    );
+}
+#[test]
+fn pem_amid_other_text() {
+    let text = format!(
+        "Here is the blob:\r\n\n  {}\nThanks!",
+        encode(Encoding::Pem, INPUT).replace('\n', "\r\n")
+    );
+    assert_eq!(decode(Encoding::Pem, text).unwrap(), INPUT);
+}
+#[test]
+fn pem_missing_markers() {
+    let text = encode(Encoding::Pem, INPUT);
+    let (_, body) = text.split_once('\n').unwrap();
+    assert_eq!(decode(Encoding::Pem, body), Err(ArmorError::MissingBegin));
+    let truncated = &text[..text.len() - 10];
+    assert_eq!(
+        decode(Encoding::Pem, truncated),
+        Err(ArmorError::MissingEnd)
+    );
+}
+#[test]
+fn invalid_text() {
+    assert_eq!(
+        decode(Encoding::Hex, "6g"),
+        Err(ArmorError::InvalidCharacter(b'g'))
+    );
+    assert_eq!(decode(Encoding::Hex, "666"), Err(ArmorError::Truncated));
+    assert_eq!(
+        decode(Encoding::Base64, "Zm9vY"),
+        Err(ArmorError::Truncated)
+    );
+    assert_eq!(
+        decode(Encoding::Base64, "Zg="),
+        Err(ArmorError::InvalidPadding)
+    );
+    assert_eq!(
+        decode(Encoding::Base64, "Z==="),
+        Err(ArmorError::InvalidPadding)
+    );
+    assert_eq!(
+        decode(Encoding::Base64, "Zg==Zg=="),
+        Err(ArmorError::InvalidPadding)
+    );
+    assert_eq!(
+        decode(Encoding::Base64, "Zm9v!"),
+        Err(ArmorError::InvalidCharacter(b'!'))
+    );
+}
+#[test]
+fn parse_encoding_names() {
+    for encoding in [
+        Encoding::Hex,
+        Encoding::Base64,
+        Encoding::Base64Url,
+        Encoding::Pem,
+    ] {
+        assert_eq!(encoding.to_string().parse(), Ok(encoding));
+    }
+    assert!("rot13".parse::<Encoding>().is_err());
+}
+#[test]
+fn writer_matches_encode() {
+    let data = all_bytes();
+    for encoding in [Encoding::Base64, Encoding::Pem] {
+        let mut writer = EncodeWriter::new(encoding, Vec::new());
+        for chunk in data.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        let text = writer.finish().unwrap();
+        assert_eq!(text, encode(encoding, &data).into_bytes());
+    }
+}
+#[test]
+fn reader_with_short_reads() {
+    let data = all_bytes();
+    let text = encode(Encoding::Pem, &data);
+    for chunk in [1, 3, 64, 65, 1000] {
+        let reader = ShortReader {
+            inner: text.as_bytes(),
+            chunk,
+        };
+        let mut out = Vec::new();
+        DecodeReader::new(Encoding::Pem, reader)
+            .read_to_end(&mut out)
+            .unwrap();
+        assert_eq!(out, data, "chunk {}", chunk);
+    }
+}
+#[test]
+fn reader_failure_repeats() {
+    let mut reader = DecodeReader::new(Encoding::Hex, &b"66 zz 66"[..]);
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(reader.read(&mut [0; 4]).is_err());
+}
+#[test]
+fn armored_munging() {
+    let mut writer = Xorcism::new("abcde").writer(EncodeWriter::new(Encoding::Pem, Vec::new()));
+    writer.write_all(INPUT).unwrap();
+    let (encoder, _) = writer.into_inner();
+    let text = encoder.finish().unwrap();
+    assert!(text.is_ascii());
+
+    let mut reader = Xorcism::new("abcde").reader(DecodeReader::new(Encoding::Pem, &text[..]));
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, INPUT);
+}
@@ -0,0 +1,183 @@
+#![cfg(any(feature = "futures-io", feature = "tokio"))]
+
+use exercism::xorcism::Xorcism;
+use futures::FutureExt;
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+fn munged(key: &[u8], data: &[u8]) -> Vec<u8> {
+    Xorcism::new(key).munge(data).collect()
+}
+
+fn long_input() -> Vec<u8> {
+    (0..20_000_u32).map(|n| (n * 7 + n / 251) as u8).collect()
+}
+
+#[cfg(feature = "tokio")]
+mod tokio_io {
+    use super::*;
+    use exercism::keystream::{Keystream, SplitMix64};
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn writer_munges() {
+        let (client, mut server) = duplex(64);
+        let mut writer = Xorcism::new("abcde").async_writer(client);
+        let input = long_input();
+        let mut output = Vec::new();
+        let (written, read) = tokio::join!(
+            async {
+                writer.write_all(&input).await?;
+                writer.shutdown().await
+            },
+            server.read_to_end(&mut output),
+        );
+        written.unwrap();
+        read.unwrap();
+        assert_eq!(output, munged(b"abcde", &input));
+    }
+    #[tokio::test]
+    async fn reader_munges() {
+        let (mut client, server) = duplex(7);
+        let mut reader = Xorcism::new("abcde").async_reader(server);
+        let cipher = munged(b"abcde", INPUT);
+        let mut output = Vec::new();
+        let (written, read) = tokio::join!(
+            async {
+                client.write_all(&cipher).await?;
+                client.shutdown().await
+            },
+            reader.read_to_end(&mut output),
+        );
+        written.unwrap();
+        read.unwrap();
+        assert_eq!(output, INPUT);
+    }
+    #[tokio::test]
+    async fn roundtrip_any_keystream() {
+        let (client, server) = duplex(13);
+        let mut writer = SplitMix64::new(7).async_writer(client);
+        let mut reader = SplitMix64::new(7).async_reader(server);
+        let input = long_input();
+        let mut output = Vec::new();
+        let (written, read) = tokio::join!(
+            async {
+                writer.write_all(&input).await?;
+                writer.shutdown().await
+            },
+            reader.read_to_end(&mut output),
+        );
+        written.unwrap();
+        read.unwrap();
+        assert_eq!(output, input);
+        assert_eq!(reader.into_inner().1.position(), input.len() as u64);
+    }
+    #[tokio::test]
+    async fn cancelled_write_keeps_key_position() {
+        let (client, mut server) = duplex(4);
+        let mut writer = Xorcism::new("abcde").async_writer(client);
+
+        // the first four bytes fit; the rest wait for room, until the write is dropped
+        assert!(writer.write_all(&INPUT[..10]).now_or_never().is_none());
+        let mut first = [0; 4];
+        server.read_exact(&mut first).await.unwrap();
+        assert_eq!(first, munged(b"abcde", &INPUT[..4])[..]);
+
+        let (written, read) = tokio::join!(
+            async {
+                writer.write_all(&INPUT[4..]).await?;
+                writer.shutdown().await
+            },
+            async {
+                let mut rest = Vec::new();
+                server.read_to_end(&mut rest).await.map(|_| rest)
+            },
+        );
+        written.unwrap();
+        assert_eq!(read.unwrap(), &munged(b"abcde", INPUT)[4..]);
+        assert_eq!(writer.into_inner().1.position(), INPUT.len() as u64);
+    }
+    #[tokio::test]
+    async fn pending_write_does_not_advance_key() {
+        let (client, _server) = duplex(4);
+        let mut writer = Xorcism::new("abcde").async_writer(client);
+        writer.write_all(&[0; 4]).await.unwrap();
+        assert!(writer.write(&[0; 4]).now_or_never().is_none());
+        assert_eq!(writer.into_inner().1.position(), 4);
+    }
+    #[tokio::test]
+    async fn pending_read_does_not_advance_key() {
+        let (mut client, server) = duplex(16);
+        let mut reader = Xorcism::new("abcde").async_reader(server);
+        let mut buf = [0; 8];
+        assert!(reader.read(&mut buf).now_or_never().is_none());
+
+        client
+            .write_all(&munged(b"abcde", &INPUT[..8]))
+            .await
+            .unwrap();
+        reader.read_exact(&mut buf).await.unwrap();
+        assert_eq!(buf, INPUT[..8]);
+        assert_eq!(reader.into_inner().1.position(), 8);
+    }
+}
+
+#[cfg(feature = "futures-io")]
+mod futures_io {
+    use super::*;
+    use futures::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::duplex;
+    use tokio_util::compat::{TokioAsyncReadCompatExt, TokioAsyncWriteCompatExt};
+
+    #[tokio::test]
+    async fn roundtrip() {
+        let (client, server) = duplex(13);
+        let mut writer = Xorcism::new("abcde").async_writer(client.compat_write());
+        let mut reader = Xorcism::new("abcde").async_reader(server.compat());
+        let input = long_input();
+        let mut output = Vec::new();
+        let (written, read) = tokio::join!(
+            async {
+                writer.write_all(&input).await?;
+                writer.close().await
+            },
+            reader.read_to_end(&mut output),
+        );
+        written.unwrap();
+        read.unwrap();
+        assert_eq!(output, input);
+    }
+    #[tokio::test]
+    async fn writer_munges() {
+        let mut writer = Xorcism::new("abcde").async_writer(futures::io::Cursor::new(Vec::new()));
+        writer.write_all(INPUT).await.unwrap();
+        let (cursor, xs) = writer.into_inner();
+        assert_eq!(cursor.into_inner(), munged(b"abcde", INPUT));
+        assert_eq!(xs.position(), INPUT.len() as u64);
+    }
+    #[tokio::test]
+    async fn cancelled_write_keeps_key_position() {
+        let (client, server) = duplex(4);
+        let mut writer = Xorcism::new("abcde").async_writer(client.compat_write());
+        let mut server = server.compat();
+
+        assert!(writer.write_all(&INPUT[..10]).now_or_never().is_none());
+        let mut first = [0; 4];
+        server.read_exact(&mut first).await.unwrap();
+
+        let (written, read) = tokio::join!(
+            async {
+                writer.write_all(&INPUT[4..]).await?;
+                writer.close().await
+            },
+            async {
+                let mut rest = Vec::new();
+                server.read_to_end(&mut rest).await.map(|_| rest)
+            },
+        );
+        written.unwrap();
+        let mut output = first.to_vec();
+        output.extend(read.unwrap());
+        assert_eq!(output, munged(b"abcde", INPUT));
+    }
+}
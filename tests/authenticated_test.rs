@@ -0,0 +1,152 @@
+#![cfg(feature = "io")]
+
+use exercism::authenticated::{
+    is_authentication_error, mac_key, AuthenticatedReader, AuthenticatedWriter,
+    AuthenticationError, TAG_LEN,
+};
+use exercism::keystream::SplitMix64;
+use exercism::sha256::hmac_sha256;
+use exercism::xorcism::Xorcism;
+use std::io::{self, Read, Write};
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+/// a reader which hands out at most `chunk` bytes per call, like a pipe or socket would
+struct ShortReader<R> {
+    inner: R,
+    chunk: usize,
+}
+impl<R: Read> Read for ShortReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+fn seal(key: &[u8], input: &[u8]) -> Vec<u8> {
+    let mut writer = AuthenticatedWriter::new(Xorcism::new(key), &mac_key(key), Vec::new());
+    writer.write_all(input).unwrap();
+    writer.finish().unwrap()
+}
+
+fn open(key: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = AuthenticatedReader::new(Xorcism::new(key), &mac_key(key), data);
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn authentication_error(err: io::Error) -> AuthenticationError {
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    assert!(is_authentication_error(&err));
+    *err.get_ref()
+        .and_then(|err| err.downcast_ref::<AuthenticationError>())
+        .unwrap()
+}
+
+#[test]
+fn roundtrip() {
+    let sealed = seal(b"abcde", INPUT);
+    assert_eq!(open(b"abcde", &sealed).unwrap(), INPUT);
+}
+#[test]
+fn layout() {
+    let sealed = seal(b"abcde", INPUT);
+    let munged: Vec<u8> = Xorcism::new("abcde").munge(INPUT).collect();
+    assert_eq!(&sealed[..INPUT.len()], &munged[..]);
+    assert_eq!(
+        &sealed[INPUT.len()..],
+        &hmac_sha256(&mac_key(b"abcde"), INPUT)[..]
+    );
+    assert_eq!(sealed.len(), INPUT.len() + TAG_LEN);
+}
+#[test]
+fn empty_payload() {
+    let sealed = seal(b"abcde", b"");
+    assert_eq!(sealed.len(), TAG_LEN);
+    assert_eq!(open(b"abcde", &sealed).unwrap(), b"");
+}
+#[test]
+fn short_reads() {
+    let input: Vec<u8> = (0..1000).map(|n| (n % 251) as u8).collect();
+    let sealed = seal(b"abcde", &input);
+    for chunk in [1, 7, 32, 33, 500] {
+        let reader = ShortReader {
+            inner: &sealed[..],
+            chunk,
+        };
+        let mut reader =
+            AuthenticatedReader::new(Xorcism::new("abcde"), &mac_key(b"abcde"), reader);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, input, "chunk {}", chunk);
+    }
+}
+#[test]
+fn wrong_key() {
+    let sealed = seal(b"abcde", INPUT);
+    let err = open(b"abcdf", &sealed).unwrap_err();
+    assert_eq!(authentication_error(err), AuthenticationError::TagMismatch);
+}
+#[test]
+fn tampered_payload() {
+    let mut sealed = seal(b"abcde", INPUT);
+    sealed[3] ^= 0x20;
+    let err = open(b"abcde", &sealed).unwrap_err();
+    assert_eq!(authentication_error(err), AuthenticationError::TagMismatch);
+}
+#[test]
+fn tampered_tag() {
+    let mut sealed = seal(b"abcde", INPUT);
+    let last = sealed.len() - 1;
+    sealed[last] ^= 1;
+    let err = open(b"abcde", &sealed).unwrap_err();
+    assert_eq!(authentication_error(err), AuthenticationError::TagMismatch);
+}
+#[test]
+fn truncated() {
+    let sealed = seal(b"abcde", INPUT);
+    let err = open(b"abcde", &sealed[..sealed.len() - 1]).unwrap_err();
+    assert_eq!(authentication_error(err), AuthenticationError::TagMismatch);
+    let err = open(b"abcde", &sealed[..TAG_LEN - 1]).unwrap_err();
+    assert_eq!(authentication_error(err), AuthenticationError::Truncated);
+}
+#[test]
+fn failure_repeats() {
+    let mut sealed = seal(b"abcde", INPUT);
+    sealed[0] ^= 1;
+    let mut reader =
+        AuthenticatedReader::new(Xorcism::new("abcde"), &mac_key(b"abcde"), &sealed[..]);
+    let mut out = Vec::new();
+    assert!(reader.read_to_end(&mut out).is_err());
+    assert!(reader.read(&mut [0; 16]).is_err());
+}
+#[test]
+fn copy_fails_loudly() {
+    let mut sealed = seal(b"abcde", INPUT);
+    sealed[10] ^= 1;
+    let mut reader =
+        AuthenticatedReader::new(Xorcism::new("abcde"), &mac_key(b"abcde"), &sealed[..]);
+    let err = io::copy(&mut reader, &mut io::sink()).unwrap_err();
+    assert!(is_authentication_error(&err));
+}
+#[test]
+fn other_errors_are_not_authentication_errors() {
+    let err = io::Error::new(io::ErrorKind::PermissionDenied, "something else");
+    assert!(!is_authentication_error(&err));
+}
+#[test]
+fn any_keystream() {
+    let mac = [7; 32];
+    let mut writer = AuthenticatedWriter::new(SplitMix64::new(1), &mac, Vec::new());
+    writer.write_all(INPUT).unwrap();
+    let sealed = writer.finish().unwrap();
+
+    let mut reader = AuthenticatedReader::new(SplitMix64::new(1), &mac, &sealed[..]);
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, INPUT);
+
+    let mut reader = AuthenticatedReader::new(SplitMix64::new(1), &[8; 32], &sealed[..]);
+    assert!(reader.read_to_end(&mut Vec::new()).is_err());
+}
@@ -0,0 +1,131 @@
+use exercism::checkpoint::{
+    fingerprint, Checkpoint, CheckpointError, CHECKPOINT_LEN, FINGERPRINT_LEN, VERSION,
+};
+use exercism::xorcism::Xorcism;
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+fn munged(key: &[u8]) -> Vec<u8> {
+    Xorcism::new(key).munge(INPUT).collect()
+}
+
+#[test]
+fn resume_carries_on() {
+    let mut xs = Xorcism::new("abcde");
+    let mut output: Vec<u8> = xs.munge(&INPUT[..20]).collect();
+    let saved = xs.checkpoint().to_bytes();
+
+    let (checkpoint, key) = Checkpoint::from_bytes(&saved).unwrap();
+    assert_eq!(key, None);
+    let mut xs = Xorcism::resume("abcde", &checkpoint).unwrap();
+    assert_eq!(xs.position(), 20);
+    output.extend(xs.munge(&INPUT[20..]));
+    assert_eq!(output, munged(b"abcde"));
+}
+#[test]
+fn layout() {
+    let mut xs = Xorcism::new("abcde");
+    xs.seek_to(1000);
+    let bytes = xs.checkpoint().to_bytes();
+    assert_eq!(bytes.len(), CHECKPOINT_LEN);
+    assert_eq!(bytes[0], VERSION);
+    assert_eq!(bytes[1], 0);
+    assert_eq!(&bytes[2..2 + FINGERPRINT_LEN], &fingerprint(b"abcde"));
+    assert_eq!(&bytes[10..18], &5_u64.to_be_bytes());
+    assert_eq!(&bytes[18..26], &1000_u64.to_be_bytes());
+}
+#[test]
+fn key_is_left_out() {
+    let key = b"a key which must not leak";
+    let bytes = Xorcism::new(key).checkpoint().to_bytes();
+    assert!(!bytes
+        .windows(4)
+        .any(|window| key.windows(4).any(|k| k == window)));
+}
+#[test]
+fn wrong_key() {
+    let checkpoint = Xorcism::new("abcde").checkpoint();
+    for key in ["abcdf", "abcd", "abcdea"] {
+        assert_eq!(
+            Xorcism::resume(key, &checkpoint).map(|_| ()),
+            Err(CheckpointError::WrongKey)
+        );
+    }
+    assert_eq!(
+        Xorcism::resume("", &checkpoint).map(|_| ()),
+        Err(CheckpointError::WrongKey)
+    );
+}
+#[test]
+fn owned_key() {
+    let mut xs = Xorcism::with_key(vec![9, 8, 7]);
+    xs.munge_in_place(&mut [0; 10]);
+    let checkpoint = xs.checkpoint();
+    let resumed = Xorcism::resume(vec![9, 8, 7], &checkpoint).unwrap();
+    assert_eq!(resumed.position(), 10);
+    assert_eq!(resumed.checkpoint(), checkpoint);
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn key_included_on_request() {
+    let mut xs = Xorcism::new("abcde");
+    xs.seek_to(7);
+    let bytes = xs.checkpoint().to_bytes_with_key(b"abcde").unwrap();
+    assert_eq!(bytes.len(), CHECKPOINT_LEN + 5);
+    assert_eq!(bytes[1], 1);
+    assert_eq!(&bytes[CHECKPOINT_LEN..], b"abcde");
+
+    let (checkpoint, key) = Checkpoint::from_bytes(&bytes).unwrap();
+    assert_eq!(key, Some(&b"abcde"[..]));
+    let xs = Xorcism::resume(key.unwrap(), &checkpoint).unwrap();
+    assert_eq!(xs.position(), 7);
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn key_included_must_match() {
+    let checkpoint = Xorcism::new("abcde").checkpoint();
+    assert_eq!(
+        checkpoint.to_bytes_with_key(b"abcdf"),
+        Err(CheckpointError::WrongKey)
+    );
+
+    let bytes = checkpoint.to_bytes_with_key(b"abcde").unwrap();
+    assert_eq!(
+        Checkpoint::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(CheckpointError::Truncated)
+    );
+
+    let mut bytes = bytes;
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    assert_eq!(
+        Checkpoint::from_bytes(&bytes),
+        Err(CheckpointError::WrongKey)
+    );
+}
+#[test]
+fn malformed() {
+    let bytes = Xorcism::new("abcde").checkpoint().to_bytes();
+    assert_eq!(
+        Checkpoint::from_bytes(&bytes[..CHECKPOINT_LEN - 1]),
+        Err(CheckpointError::Truncated)
+    );
+    assert_eq!(
+        Checkpoint::from_bytes(&[&bytes[..], b"x"].concat()),
+        Err(CheckpointError::TrailingBytes)
+    );
+
+    let mut newer = bytes;
+    newer[0] = VERSION + 1;
+    assert_eq!(
+        Checkpoint::from_bytes(&newer),
+        Err(CheckpointError::UnsupportedVersion(VERSION + 1))
+    );
+
+    let mut flagged = bytes;
+    flagged[1] = 0x80;
+    assert_eq!(
+        Checkpoint::from_bytes(&flagged),
+        Err(CheckpointError::UnknownFlags(0x80))
+    );
+}
@@ -0,0 +1,399 @@
+#![cfg(feature = "io")]
+
+use exercism::armor::{encode, Encoding};
+use exercism::xorcism::Xorcism;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+/// Few enough passphrase iterations to keep the tests quick in unoptimized builds
+const ITERATIONS: &str = "100";
+
+fn xorcism(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_xorcism"))
+        .args(args)
+        .env("XORCISM_TEST_KEY", "abcde")
+        .env("XORCISM_PASSPHRASE", "correct horse battery staple")
+        .env("XORCISM_ITERATIONS", ITERATIONS)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // the child may exit without reading stdin, e.g. on a usage error
+    let _ = child.stdin.take().unwrap().write_all(stdin);
+    child.wait_with_output().unwrap()
+}
+
+fn munged(key: &[u8], offset: u64) -> Vec<u8> {
+    let mut xs = Xorcism::new(key);
+    xs.seek_to(offset);
+    xs.munge(INPUT).collect()
+}
+
+/// a fresh path in the test scratch directory
+fn scratch(name: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn literal_key() {
+    let output = xorcism(&["--key", "abcde"], INPUT);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, munged(b"abcde", 0));
+}
+#[test]
+fn hex_key() {
+    let output = xorcism(&["-x", "61 62 63 64 65"], INPUT);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, munged(b"abcde", 0));
+}
+#[test]
+fn key_file() {
+    let path = scratch("key_file.key");
+    std::fs::write(&path, [0, 255, 7]).unwrap();
+    let output = xorcism(&["--key-file", path.to_str().unwrap()], INPUT);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, munged(&[0, 255, 7], 0));
+}
+#[test]
+fn env_key() {
+    let output = xorcism(&["-e", "XORCISM_TEST_KEY"], INPUT);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, munged(b"abcde", 0));
+}
+#[test]
+fn offset() {
+    let output = xorcism(&["-k", "abcde", "--offset", "3"], INPUT);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, munged(b"abcde", 3));
+}
+#[test]
+fn files_roundtrip() {
+    let input = scratch("files_roundtrip.txt");
+    let cipher = scratch("files_roundtrip.bin");
+    std::fs::write(&input, INPUT).unwrap();
+
+    let output = xorcism(
+        &[
+            "-k",
+            "abcde",
+            input.to_str().unwrap(),
+            "-o",
+            cipher.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    assert_eq!(std::fs::read(&cipher).unwrap(), munged(b"abcde", 0));
+
+    let output = xorcism(&["-k", "abcde", cipher.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, INPUT);
+}
+#[test]
+fn in_place() {
+    let path = scratch("in_place.txt");
+    std::fs::write(&path, INPUT).unwrap();
+
+    let output = xorcism(&["-k", "abcde", "--in-place", path.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(std::fs::read(&path).unwrap(), munged(b"abcde", 0));
+
+    let output = xorcism(&["-k", "abcde", "-i", path.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert_eq!(std::fs::read(&path).unwrap(), INPUT);
+}
+#[test]
+fn usage_errors() {
+    for args in [
+        &[][..],
+        &["-k", "abc", "-x", "616263"],
+        &["-k", ""],
+        &["-x", "6"],
+        &["-x", "zz"],
+        &["-k", "abc", "--offset", "-1"],
+        &["-k", "abc", "--in-place"],
+        &["-k", "abc", "--frobnicate"],
+        &["-k", "abc", "--salt-file", "salt"],
+        &["-k", "abc", "--encode", "rot13"],
+        &["-k", "abc", "--decode"],
+        &["-p"],
+    ] {
+        let output = xorcism(args, INPUT);
+        assert_eq!(output.status.code(), Some(2), "args {:?}", args);
+        assert!(output.stdout.is_empty());
+        assert!(!output.stderr.is_empty());
+    }
+}
+#[test]
+fn output_same_as_input() {
+    let path = scratch("output_same_as_input.txt");
+    std::fs::write(&path, INPUT).unwrap();
+    let same = path.with_file_name(".").join("output_same_as_input.txt");
+    let output = xorcism(
+        &[
+            "-k",
+            "abc",
+            path.to_str().unwrap(),
+            "-o",
+            same.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(std::fs::read(&path).unwrap(), INPUT);
+}
+#[test]
+fn encode_and_decode() {
+    for (format, text) in [
+        ("hex", encode(Encoding::Hex, &munged(b"abcde", 0)) + "\n"),
+        (
+            "base64",
+            encode(Encoding::Base64, &munged(b"abcde", 0)) + "\n",
+        ),
+        (
+            "base64url",
+            encode(Encoding::Base64Url, &munged(b"abcde", 0)) + "\n",
+        ),
+        ("pem", encode(Encoding::Pem, &munged(b"abcde", 0))),
+    ] {
+        let output = xorcism(&["-k", "abcde", "--encode", format], INPUT);
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), text);
+
+        let output = xorcism(&["-k", "abcde", "--decode", format], text.as_bytes());
+        assert!(output.status.success());
+        assert_eq!(output.stdout, INPUT);
+    }
+}
+#[test]
+fn encode_in_place() {
+    let path = scratch("encode_in_place.txt");
+    std::fs::write(&path, INPUT).unwrap();
+
+    let output = xorcism(
+        &[
+            "-k",
+            "abcde",
+            "-i",
+            "--encode",
+            "pem",
+            path.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("-----BEGIN XORCISM DATA-----\n"));
+
+    let output = xorcism(
+        &[
+            "-k",
+            "abcde",
+            "-i",
+            "--decode",
+            "pem",
+            path.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    assert_eq!(std::fs::read(&path).unwrap(), INPUT);
+}
+#[test]
+fn decode_errors() {
+    let output = xorcism(&["-k", "abcde", "--decode", "hex"], b"not hex");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid character"));
+}
+#[test]
+fn missing_input_file() {
+    let path = scratch("missing_input_file.txt");
+    let output = xorcism(&["-k", "abc", path.to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing_input_file.txt"));
+}
+#[test]
+fn crack() {
+    let key: Vec<u8> = (0..64_u32).map(|i| (i * 151 + 89) as u8).collect();
+    let first = scratch("crack_first.bin");
+    let second = scratch("crack_second.bin");
+    std::fs::write(&first, munged(&key, 0)).unwrap();
+    let second_plain = b"Another message entirely, with the same key.";
+    let second_cipher: Vec<u8> = Xorcism::new(&key).munge(second_plain).collect();
+    std::fs::write(&second, second_cipher).unwrap();
+
+    let commands =
+        "drag super\nplace 0 0 This is super-secret, cutting edge\nshow\nkey\nbogus\nquit\n";
+    let output = xorcism(
+        &["crack", first.to_str().unwrap(), second.to_str().unwrap()],
+        commands.as_bytes(),
+    );
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // "super" sits at offset 8 of the first message, over "messa" in the second
+    assert!(stdout.contains("messages 0 & 1  offset     8  \"messa\""));
+    assert!(stdout.contains("  0: This is super-secret, cutting edge__________________"));
+    assert!(stdout.contains("  1: Another message entirely, with the__________"));
+    let known: String = key[..34]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    assert!(stdout.contains(&format!("{}{}", known, "??".repeat(19))));
+    assert!(stdout.contains("unknown command \"bogus\""));
+}
+#[test]
+fn crack_needs_two_files() {
+    let output = xorcism(&["crack", "one"], b"");
+    assert_eq!(output.status.code(), Some(2));
+}
+#[test]
+fn crack_with_corpus() {
+    let corpus = scratch("crack_corpus.txt");
+    let first = scratch("crack_corpus_first.bin");
+    let second = scratch("crack_corpus_second.bin");
+    std::fs::write(&corpus, "status=200 path=/api status=404 path=/login").unwrap();
+    std::fs::write(&first, b"\x01\x02\x03\x04").unwrap();
+    std::fs::write(&second, b"\x05\x06\x07\x08").unwrap();
+    let files = [first.to_str().unwrap(), second.to_str().unwrap()];
+
+    let output = xorcism(
+        &[&["crack", "--corpus", corpus.to_str().unwrap()][..], &files].concat(),
+        b"show\n",
+    );
+    assert!(output.status.success());
+
+    let missing = scratch("crack_corpus_missing.txt");
+    let output = xorcism(
+        &[
+            &["crack", "--corpus", missing.to_str().unwrap()][..],
+            &files,
+        ]
+        .concat(),
+        b"",
+    );
+    assert_eq!(output.status.code(), Some(1));
+}
+#[test]
+fn passphrase_roundtrip() {
+    let input = scratch("passphrase_roundtrip.txt");
+    let cipher = scratch("passphrase_roundtrip.bin");
+    let salt = scratch("passphrase_roundtrip.bin.salt");
+    std::fs::write(&input, INPUT).unwrap();
+
+    let output = xorcism(
+        &[
+            "-p",
+            input.to_str().unwrap(),
+            "-o",
+            cipher.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(output.status.success());
+    // the salt file holds the iteration count, big-endian, then the salt
+    let salt_bytes = std::fs::read(&salt).unwrap();
+    assert_eq!(salt_bytes.len(), 4 + exercism::kdf::SALT_LEN);
+    let iterations: u32 = ITERATIONS.parse().unwrap();
+    assert_eq!(salt_bytes[..4], iterations.to_be_bytes());
+    let key = exercism::kdf::derive_key(
+        "correct horse battery staple",
+        &salt_bytes[4..],
+        iterations,
+        64,
+    );
+    assert_eq!(std::fs::read(&cipher).unwrap(), munged(&key, 0));
+
+    // munging back reads the salt from beside the input
+    let output = xorcism(&["--passphrase", cipher.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, INPUT);
+}
+#[test]
+fn passphrase_iterations_from_salt_file() {
+    let cipher = scratch("passphrase_iterations.bin");
+    let salt = scratch("passphrase_iterations.bin.salt");
+    std::fs::write(&salt, [0, 0, 0, 3, 1, 2, 3, 4]).unwrap();
+    let key = exercism::kdf::derive_key("correct horse battery staple", &[1, 2, 3, 4], 3, 64);
+    std::fs::write(&cipher, munged(&key, 0)).unwrap();
+
+    // the count in the salt file wins over XORCISM_ITERATIONS
+    let output = xorcism(&["-p", cipher.to_str().unwrap()], b"");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, INPUT);
+
+    std::fs::write(&salt, [0, 0, 3]).unwrap();
+    let output = xorcism(&["-p", cipher.to_str().unwrap()], b"");
+    assert_eq!(output.status.code(), Some(1));
+}
+#[test]
+fn passphrase_salts_differ() {
+    let first = scratch("passphrase_salts_first.bin");
+    let second = scratch("passphrase_salts_second.bin");
+    let _ = scratch("passphrase_salts_first.bin.salt");
+    let _ = scratch("passphrase_salts_second.bin.salt");
+    assert!(xorcism(&["-p", "-o", first.to_str().unwrap()], INPUT)
+        .status
+        .success());
+    assert!(xorcism(&["-p", "-o", second.to_str().unwrap()], INPUT)
+        .status
+        .success());
+    assert_ne!(
+        std::fs::read(&first).unwrap(),
+        std::fs::read(&second).unwrap()
+    );
+}
+#[test]
+fn passphrase_salt_file() {
+    let salt = scratch("passphrase_salt_file.salt");
+    let output = xorcism(&["-p", "--salt-file", salt.to_str().unwrap()], INPUT);
+    assert!(output.status.success());
+    assert!(salt.exists());
+
+    let output = xorcism(
+        &["-p", "--salt-file", salt.to_str().unwrap()],
+        &output.stdout,
+    );
+    assert!(output.status.success());
+    assert_eq!(output.stdout, INPUT);
+}
+#[test]
+fn passphrase_in_place() {
+    let path = scratch("passphrase_in_place.txt");
+    let salt = scratch("passphrase_in_place.txt.salt");
+    std::fs::write(&path, INPUT).unwrap();
+
+    assert!(xorcism(&["-p", "-i", path.to_str().unwrap()], b"")
+        .status
+        .success());
+    assert!(salt.exists());
+    assert_ne!(std::fs::read(&path).unwrap(), INPUT);
+
+    assert!(xorcism(&["-p", "-i", path.to_str().unwrap()], b"")
+        .status
+        .success());
+    assert_eq!(std::fs::read(&path).unwrap(), INPUT);
+}
+#[test]
+fn passphrase_missing_input_leaves_no_salt() {
+    let input = scratch("passphrase_missing_input.txt");
+    let cipher = scratch("passphrase_missing_input.bin");
+    let salt = scratch("passphrase_missing_input.bin.salt");
+    let output = xorcism(
+        &[
+            "-p",
+            input.to_str().unwrap(),
+            "-o",
+            cipher.to_str().unwrap(),
+        ],
+        b"",
+    );
+    assert!(!output.status.success());
+    assert!(!salt.exists());
+}
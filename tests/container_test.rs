@@ -0,0 +1,199 @@
+#![cfg(feature = "io")]
+
+use exercism::container::{
+    fingerprint, ContainerError, ContainerReader, ContainerWriter, Header, MAGIC, VERSION,
+};
+use exercism::crc32::{crc32, Crc32};
+use exercism::kdf::derive_key;
+use exercism::xorcism::Xorcism;
+use std::io::{self, Read, Write};
+
+const INPUT: &[u8] = b"This is super-secret, cutting edge encryption, folks.";
+
+/// a reader which hands out at most `chunk` bytes per call, like a pipe or socket would
+struct ShortReader<R> {
+    inner: R,
+    chunk: usize,
+}
+impl<R: Read> Read for ShortReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+fn container(key: &[u8], offset: u64, salt: Option<&[u8]>, input: &[u8]) -> Vec<u8> {
+    let mut xs = Xorcism::new(key);
+    xs.seek_to(offset);
+    let mut writer = ContainerWriter::new(xs, salt, Vec::new()).unwrap();
+    writer.write_all(input).unwrap();
+    writer.finish().unwrap()
+}
+
+fn open(key: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut reader = ContainerReader::open(Xorcism::new(key), data)?;
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+fn container_error(err: io::Error) -> ContainerError {
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    *err.get_ref()
+        .and_then(|err| err.downcast_ref::<ContainerError>())
+        .unwrap()
+}
+
+#[test]
+fn crc32_check_value() {
+    assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    assert_eq!(crc32(b""), 0);
+    let mut crc = Crc32::new();
+    crc.update(b"12345");
+    crc.update(b"6789");
+    assert_eq!(crc.value(), 0xcbf43926);
+}
+#[test]
+fn roundtrip() {
+    let data = container(b"abcde", 0, None, INPUT);
+    assert_eq!(open(b"abcde", &data).unwrap(), INPUT);
+}
+#[test]
+fn layout() {
+    let data = container(b"abcde", 3, Some(b"pepper"), INPUT);
+    assert_eq!(&data[..4], &MAGIC);
+    assert_eq!(data[4], VERSION);
+    assert_eq!(data[5], 6);
+    assert_eq!(&data[6..14], &fingerprint(b"abcde"));
+    assert_eq!(&data[14..18], &5_u32.to_be_bytes());
+    assert_eq!(&data[18..26], &3_u64.to_be_bytes());
+    assert_eq!(&data[26..32], b"pepper");
+
+    let mut xs = Xorcism::new("abcde");
+    xs.seek_to(3);
+    let payload: Vec<u8> = xs.munge(INPUT).collect();
+    assert_eq!(&data[32..32 + INPUT.len()], &payload[..]);
+
+    let footer = &data[32 + INPUT.len()..];
+    assert_eq!(&footer[..8], &(INPUT.len() as u64).to_be_bytes());
+    assert_eq!(&footer[8..], &crc32(INPUT).to_be_bytes());
+}
+#[test]
+fn header_records_offset_and_salt() {
+    let data = container(b"abcde", 1000, Some(b"pepper"), INPUT);
+    let mut reader = ContainerReader::open(Xorcism::new("abcde"), &data[..]).unwrap();
+    assert_eq!(
+        reader.header(),
+        &Header {
+            version: VERSION,
+            fingerprint: fingerprint(b"abcde"),
+            key_len: 5,
+            offset: 1000,
+            salt: Some(b"pepper".to_vec()),
+        }
+    );
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, INPUT);
+}
+#[test]
+fn empty_payload() {
+    let data = container(b"abcde", 0, None, b"");
+    assert_eq!(open(b"abcde", &data).unwrap(), b"");
+}
+#[test]
+fn short_reads() {
+    let input: Vec<u8> = (0..1000).map(|n| (n % 251) as u8).collect();
+    let data = container(b"a longer key than most", 7, None, &input);
+    for chunk in [1, 5, 12, 13, 100] {
+        let reader = ShortReader {
+            inner: &data[..],
+            chunk,
+        };
+        let mut reader =
+            ContainerReader::open(Xorcism::new("a longer key than most"), reader).unwrap();
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, input, "chunk {}", chunk);
+    }
+}
+#[test]
+fn passphrase_salt_is_read_before_the_key() {
+    let salt = b"0123456789abcdef";
+    let key = derive_key("passphrase", salt, 10, 32);
+    let data = container(&key, 0, Some(salt), INPUT);
+
+    let mut data = &data[..];
+    let header = Header::read_from(&mut data).unwrap();
+    let key = derive_key("passphrase", header.salt.as_deref().unwrap(), 10, 32);
+    let mut reader = ContainerReader::new(header, Xorcism::with_key(key), data).unwrap();
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, INPUT);
+}
+#[test]
+fn wrong_key() {
+    let data = container(b"abcde", 0, None, INPUT);
+    for key in [&b"abcdf"[..], b"abcd", b"abcdea"] {
+        let err = open(key, &data).map(|_| ()).unwrap_err();
+        assert_eq!(container_error(err), ContainerError::WrongKey);
+    }
+}
+#[test]
+fn not_a_container() {
+    let err = open(b"abcde", &[0; 64]).unwrap_err();
+    assert_eq!(container_error(err), ContainerError::BadMagic);
+}
+#[test]
+fn unsupported_version() {
+    let mut data = container(b"abcde", 0, None, INPUT);
+    data[4] = VERSION + 1;
+    let err = open(b"abcde", &data).unwrap_err();
+    assert_eq!(
+        container_error(err),
+        ContainerError::UnsupportedVersion(VERSION + 1)
+    );
+}
+#[test]
+fn truncated_header() {
+    let data = container(b"abcde", 0, None, INPUT);
+    let err = open(b"abcde", &data[..20]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+#[test]
+fn truncated_payload() {
+    let data = container(b"abcde", 0, None, INPUT);
+    let err = open(b"abcde", &data[..data.len() - 20]).unwrap_err();
+    assert!(matches!(
+        container_error(err),
+        ContainerError::LengthMismatch { .. }
+    ));
+
+    let err = open(b"abcde", &data[..30]).unwrap_err();
+    assert_eq!(container_error(err), ContainerError::Truncated);
+}
+#[test]
+fn unfinished_container() {
+    let mut writer = ContainerWriter::new(Xorcism::new("abcde"), None, Vec::new()).unwrap();
+    writer.write_all(INPUT).unwrap();
+    let err = open(b"abcde", writer.get_ref()).unwrap_err();
+    assert!(matches!(
+        container_error(err),
+        ContainerError::LengthMismatch { .. }
+    ));
+}
+#[test]
+fn corrupted_payload() {
+    let mut data = container(b"abcde", 0, None, INPUT);
+    data[40] ^= 1;
+    let err = open(b"abcde", &data).unwrap_err();
+    assert_eq!(container_error(err), ContainerError::ChecksumMismatch);
+}
+#[test]
+fn salt_too_long() {
+    let salt = [0; 256];
+    let err = ContainerWriter::new(Xorcism::new("abcde"), Some(&salt), Vec::new())
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,77 @@
+#[cfg(feature = "alloc")]
+use exercism::kdf::derive_key;
+use exercism::kdf::pbkdf2_hmac_sha256;
+#[cfg(feature = "std")]
+use exercism::kdf::{random_salt, SALT_LEN};
+#[cfg(feature = "std")]
+use exercism::xorcism::Xorcism;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn pbkdf2_rfc7914_single_iteration() {
+    let mut out = [0; 64];
+    pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut out);
+    assert_eq!(
+        hex(&out),
+        "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+         49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+    );
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn pbkdf2_4096_iterations() {
+    assert_eq!(
+        hex(&derive_key("password", b"salt", 4096, 32)),
+        "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a"
+    );
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn pbkdf2_zero_iterations_is_one() {
+    assert_eq!(
+        derive_key("passwd", b"salt", 0, 64),
+        derive_key("passwd", b"salt", 1, 64)
+    );
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn key_length_is_honoured() {
+    for key_len in [0, 1, 31, 32, 33, 100] {
+        let key = derive_key("correct horse battery staple", b"salt", 2, key_len);
+        assert_eq!(key.len(), key_len);
+    }
+    // longer keys extend shorter ones
+    let short = derive_key("correct horse battery staple", b"salt", 2, 40);
+    let long = derive_key("correct horse battery staple", b"salt", 2, 100);
+    assert_eq!(short, &long[..40]);
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn salt_changes_key() {
+    assert_ne!(
+        derive_key("passphrase", b"salt one", 10, 32),
+        derive_key("passphrase", b"salt two", 10, 32)
+    );
+}
+#[test]
+#[cfg(feature = "std")]
+fn random_salts_differ() {
+    let a = random_salt();
+    let b = random_salt();
+    assert_eq!(a.len(), SALT_LEN);
+    assert_ne!(a, b);
+}
+#[test]
+#[cfg(feature = "std")]
+fn derived_key_munges() {
+    let salt = random_salt();
+    let input = b"typed, not generated";
+    let key = derive_key("passphrase", &salt, 10, 32);
+    let munged: Vec<u8> = Xorcism::new(&key).munge(input).collect();
+    let key = derive_key("passphrase", &salt, 10, 32);
+    let unmunged: Vec<u8> = Xorcism::new(&key).munge(&munged).collect();
+    assert_eq!(unmunged, input);
+}
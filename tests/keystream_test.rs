@@ -0,0 +1,203 @@
+use exercism::keystream::Keystream;
+use exercism::xorcism::Xorcism;
+#[cfg(feature = "io")]
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+/// a keystream which isn't a repeating key: byte `n` is `n * 7 + 3`, wrapping
+#[derive(Default)]
+struct Ramp {
+    pos: u64,
+    seeks: usize,
+}
+impl Ramp {
+    fn at(offset: u64) -> u8 {
+        (offset.wrapping_mul(7) + 3) as u8
+    }
+}
+impl Keystream for Ramp {
+    fn next_byte(&mut self) -> u8 {
+        let byte = Ramp::at(self.pos);
+        self.pos += 1;
+        byte
+    }
+
+    fn seek_to(&mut self, offset: u64) {
+        self.pos = offset;
+        self.seeks += 1;
+    }
+
+    fn position(&self) -> u64 {
+        self.pos
+    }
+}
+
+/// a writer which accepts at most `chunk` bytes per call, and fails once it has `limit` bytes
+#[cfg(feature = "io")]
+struct ShortWriter {
+    inner: Vec<u8>,
+    chunk: usize,
+    limit: usize,
+}
+#[cfg(feature = "io")]
+impl Write for ShortWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.inner.len() >= self.limit {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        let len = buf.len().min(self.chunk);
+        self.inner.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn expected(data: &[u8], start: u64) -> Vec<u8> {
+    data.iter()
+        .zip(start..)
+        .map(|(byte, offset)| byte ^ Ramp::at(offset))
+        .collect()
+}
+
+fn munge_with<Stream: Keystream>(stream: &mut Stream, data: &[u8]) -> Vec<u8> {
+    stream.munge(data).collect()
+}
+
+#[test]
+fn munge_uses_next_byte() {
+    let data = b"any stream of bytes will do";
+    let mut ramp = Ramp::default();
+    assert_eq!(ramp.munge(data).collect::<Vec<_>>(), expected(data, 0));
+    assert_eq!(ramp.position(), data.len() as u64);
+}
+#[test]
+fn default_munge_in_place_matches_munge() {
+    for len in [0, 1, 7, 8, 9, 511, 512, 513, 1500] {
+        let data: Vec<u8> = (0..len).map(|n| (n % 251) as u8).collect();
+        let mut ramp = Ramp::default();
+        ramp.seek_to(5);
+        let mut munged = data.clone();
+        ramp.munge_in_place(&mut munged);
+        assert_eq!(munged, expected(&data, 5), "len {}", len);
+        assert_eq!(ramp.position(), 5 + len as u64);
+    }
+}
+#[test]
+fn default_fill_takes_next_bytes() {
+    let mut ramp = Ramp::default();
+    ramp.seek_to(100);
+    let mut buf = [0; 10];
+    ramp.fill(&mut buf);
+    assert_eq!(buf.to_vec(), expected(&[0; 10], 100));
+    assert_eq!(ramp.position(), 110);
+}
+#[test]
+fn munge_only_advances_past_yielded_bytes() {
+    let data = b"lazy as ever";
+    let mut ramp = Ramp::default();
+    assert_eq!(ramp.munge(data).take(4).count(), 4);
+    assert_eq!(ramp.position(), 4);
+    assert_eq!(
+        ramp.munge(&data[4..]).collect::<Vec<_>>(),
+        expected(&data[4..], 4)
+    );
+}
+#[test]
+fn xorcism_is_a_keystream() {
+    let key = "abcde";
+    let data = b"This is super-secret, cutting edge encryption, folks.";
+    let mut generic = Xorcism::new(key);
+    let mut inherent = Xorcism::new(key);
+    assert_eq!(
+        munge_with(&mut generic, data),
+        inherent.munge(data).collect::<Vec<_>>()
+    );
+    assert_eq!(Keystream::position(&generic), inherent.position());
+}
+#[test]
+fn xorcism_fill_yields_key() {
+    let mut xs = Xorcism::new("abc");
+    Keystream::seek_to(&mut xs, 1);
+    let mut buf = [0; 7];
+    xs.fill(&mut buf);
+    assert_eq!(&buf, b"bcabcab");
+    assert_eq!(xs.next_byte(), b'c');
+}
+#[test]
+#[cfg(feature = "alloc")]
+fn boxed_keystreams_munge() {
+    let data = b"chosen at runtime";
+    let mut streams: Vec<Box<dyn Keystream>> = vec![
+        Box::new(Ramp::default()),
+        Box::new(Xorcism::with_key(vec![9, 8, 7])),
+    ];
+    let mut ramp_out = data.to_vec();
+    streams[0].munge_in_place(&mut ramp_out);
+    assert_eq!(ramp_out, expected(data, 0));
+
+    let mut xs = Xorcism::with_key(vec![9, 8, 7]);
+    assert_eq!(
+        munge_with(&mut streams[1], data),
+        xs.munge(data).collect::<Vec<_>>()
+    );
+}
+#[test]
+fn borrowed_keystream_keeps_position() {
+    let data = b"borrowed for a while";
+    let mut ramp = Ramp::default();
+    munge_with(&mut &mut ramp, &data[..6]);
+    assert_eq!(ramp.position(), 6);
+}
+#[cfg(feature = "io")]
+#[test]
+fn reader_munges_with_any_keystream() {
+    let data = b"read through a ramp".to_vec();
+    let mut reader = Ramp::default().reader(Cursor::new(data.clone()));
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, expected(&data, 0));
+}
+#[cfg(feature = "io")]
+#[test]
+fn reader_seeks_any_keystream() {
+    let data = b"read through a ramp".to_vec();
+    let mut reader = Ramp::default().reader(Cursor::new(expected(&data, 0)));
+    reader.seek(SeekFrom::Start(8)).unwrap();
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, &data[8..]);
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_short_writes_with_any_keystream() {
+    let data: Vec<u8> = (0..=255).collect();
+    let sink = ShortWriter {
+        inner: Vec::new(),
+        chunk: 9,
+        limit: usize::MAX,
+    };
+    let mut writer = Ramp::default().writer(sink);
+    writer.write_all(&data).unwrap();
+    let (sink, ramp) = writer.into_inner();
+    assert_eq!(sink.inner, expected(&data, 0));
+    assert_eq!(ramp.position(), data.len() as u64);
+    // keystreams can be slow to seek, so short writes keep the keystream instead of rewinding
+    assert_eq!(ramp.seeks, 0);
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_failed_write_keeps_position() {
+    let sink = ShortWriter {
+        inner: Vec::new(),
+        chunk: 4,
+        limit: 4,
+    };
+    let mut writer = Ramp::default().writer(sink);
+    assert_eq!(writer.write(b"abcdef").unwrap(), 4);
+    assert!(writer.write(b"ef").is_err());
+    let (sink, ramp) = writer.into_inner();
+    assert_eq!(sink.inner, expected(b"abcd", 0));
+    assert_eq!(ramp.position(), 4);
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "std")]
+
+use exercism::analysis::known_plaintext::{
+    infer_key, infer_key_length, key_from_cribs, Crib, KnownPlaintextError,
+};
+use exercism::xorcism::Xorcism;
+
+const PLAINTEXT: &[u8] =
+    br#"{"version":2,"user":"mallory","role":"admin","notes":"rotate the key"}"#;
+const KEY: &[u8] = b"hunter2";
+
+fn ciphertext() -> Vec<u8> {
+    Xorcism::new(KEY).munge(PLAINTEXT).collect()
+}
+
+#[test]
+fn long_crib_gives_away_the_key() {
+    let ciphertext = ciphertext();
+    let key = infer_key(&ciphertext, &[Crib::new(0, r#"{"version":"#)], 16).unwrap();
+    assert!(key.is_complete());
+    assert_eq!(key.to_key().unwrap(), KEY);
+    assert!(key.unknown().is_empty());
+    let plaintext: Vec<u8> = Xorcism::new(&key.fill(0)).munge(&ciphertext).collect();
+    assert_eq!(plaintext, PLAINTEXT);
+}
+#[test]
+fn short_crib_gives_away_part_of_the_key() {
+    let ciphertext = ciphertext();
+    let key = key_from_cribs(&ciphertext, KEY.len(), &[Crib::new(0, r#"{"ve"#)]).unwrap();
+    assert!(!key.is_complete());
+    assert_eq!(key.to_key(), None);
+    assert_eq!(key.len(), KEY.len());
+    assert_eq!(key.unknown(), &[4, 5, 6]);
+    assert_eq!(key.get(0), Some(b'h'));
+    assert_eq!(key.get(4), None);
+    assert_eq!(key.bytes()[3], Some(b't'));
+    assert_eq!(key.to_string(), "68756e74??????");
+    assert_eq!(key.fill(b'*'), b"hunt***");
+}
+#[test]
+fn cribs_combine() {
+    let ciphertext = ciphertext();
+    // offset 15 is key index 1, so the second crib fills in key bytes from there on
+    let cribs = [Crib::new(0, "{"), Crib::new(15, r#"ser""#)];
+    let key = key_from_cribs(&ciphertext, KEY.len(), &cribs).unwrap();
+    assert_eq!(key.to_string(), "68756e7465????");
+    let cribs = [Crib::new(0, "{"), Crib::new(15, r#"ser":""#)];
+    let key = key_from_cribs(&ciphertext, KEY.len(), &cribs).unwrap();
+    assert_eq!(key.to_key().unwrap(), KEY);
+}
+#[test]
+fn scattered_cribs_give_away_the_length() {
+    let ciphertext = ciphertext();
+    let cribs = [Crib::new(1, r#""version""#), Crib::new(20, r#""mallory""#)];
+    assert_eq!(infer_key_length(&ciphertext, &cribs, 16), Ok(KEY.len()));
+}
+#[test]
+fn contradicting_cribs() {
+    let ciphertext = ciphertext();
+    // "role" really sits at offset 31, so claiming it is at 30 contradicts the first crib
+    let cribs = [Crib::new(0, r#"{"version":2,"#), Crib::new(30, "role")];
+    let err = key_from_cribs(&ciphertext, KEY.len(), &cribs).unwrap_err();
+    match err {
+        KnownPlaintextError::Contradiction {
+            key_index,
+            offsets,
+            values,
+        } => {
+            assert_eq!(key_index, offsets.0 % KEY.len());
+            assert_eq!(key_index, offsets.1 % KEY.len());
+            assert!(offsets.1 >= 30);
+            assert_ne!(values.0, values.1);
+        }
+        err => panic!("unexpected error {:?}", err),
+    }
+    assert_eq!(
+        infer_key_length(&ciphertext, &cribs, 16),
+        Err(KnownPlaintextError::NoConsistentLength)
+    );
+}
+#[test]
+fn crib_too_short_for_the_length() {
+    let ciphertext = ciphertext();
+    assert_eq!(
+        infer_key(&ciphertext, &[Crib::new(0, r#"{"v"#)], 16),
+        Err(KnownPlaintextError::LengthUndetermined)
+    );
+}
+#[test]
+fn crib_out_of_bounds() {
+    let ciphertext = ciphertext();
+    let crib = Crib::new(PLAINTEXT.len() - 2, "key\"}");
+    assert_eq!(
+        key_from_cribs(&ciphertext, KEY.len(), &[crib]),
+        Err(KnownPlaintextError::CribOutOfBounds {
+            offset: PLAINTEXT.len() - 2,
+            len: 5
+        })
+    );
+    let crib = Crib::new(usize::MAX, "x");
+    assert!(key_from_cribs(&ciphertext, KEY.len(), &[crib]).is_err());
+}
+#[test]
+fn empty_key_length() {
+    assert_eq!(
+        key_from_cribs(&ciphertext(), 0, &[]),
+        Err(KnownPlaintextError::EmptyKey)
+    );
+}
@@ -0,0 +1,155 @@
+use exercism::keystream::lfsr::{FibonacciLfsr, GaloisLfsr, LfsrError};
+use exercism::keystream::Keystream;
+#[cfg(feature = "io")]
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+/// the 16-bit maximal-length polynomial x^16 + x^14 + x^13 + x^11 + 1
+const TAPS_16: [u32; 4] = [16, 14, 13, 11];
+
+fn keystream<Stream: Keystream>(stream: &mut Stream, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    stream.fill(&mut buf);
+    buf
+}
+
+#[test]
+fn fibonacci_16_bit_vector() {
+    let mut lfsr = FibonacciLfsr::new(16, &TAPS_16, 0xace1);
+    assert_eq!(
+        keystream(&mut lfsr, 8),
+        [0xe1, 0xac, 0x22, 0x47, 0x37, 0xc4, 0x9d, 0xe3]
+    );
+    assert_eq!(lfsr.state(), 0x8815);
+}
+#[test]
+fn galois_16_bit_vector() {
+    let mut lfsr = GaloisLfsr::new(16, &TAPS_16, 0xace1);
+    assert_eq!(
+        keystream(&mut lfsr, 8),
+        [0xe1, 0xc4, 0x62, 0x3b, 0x0d, 0xbb, 0x77, 0x1f]
+    );
+    assert_eq!(lfsr.state(), 0x1bbf);
+}
+#[test]
+fn fibonacci_prbs7_vector() {
+    let mut lfsr = FibonacciLfsr::new(7, &[7, 6], 0x7f);
+    assert_eq!(
+        keystream(&mut lfsr, 8),
+        [0x7f, 0x20, 0x18, 0x8a, 0x27, 0x9a, 0x2b, 0x5f]
+    );
+}
+#[test]
+fn galois_prbs7_vector() {
+    let mut lfsr = GaloisLfsr::new(7, &[7, 6], 0x7f);
+    assert_eq!(
+        keystream(&mut lfsr, 8),
+        [0x3f, 0x10, 0x0c, 0xc5, 0x13, 0xcd, 0x95, 0x2f]
+    );
+}
+#[test]
+fn full_width_registers() {
+    let taps = [64, 63, 61, 60];
+    let mut fibonacci = FibonacciLfsr::new(64, &taps, 1);
+    assert_eq!(
+        keystream(&mut fibonacci, 16),
+        [1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0xb0]
+    );
+    let mut galois = GaloisLfsr::new(64, &taps, 1);
+    assert_eq!(
+        keystream(&mut galois, 16),
+        [1, 0, 0, 0, 0, 0, 0, 0xb0, 1, 0, 0, 0, 0, 0, 0, 0x45]
+    );
+}
+#[test]
+fn maximal_length_period() {
+    let mut fibonacci = FibonacciLfsr::new(16, &TAPS_16, 0xace1);
+    let mut galois = GaloisLfsr::new(16, &TAPS_16, 0xace1);
+    // the state repeats every 65535 bits, which first lines up with a byte after 65535 bytes
+    for _ in 1..65535 {
+        fibonacci.next_byte();
+        galois.next_byte();
+        assert_ne!(fibonacci.state(), 0xace1);
+        assert_ne!(galois.state(), 0xace1);
+    }
+    fibonacci.next_byte();
+    galois.next_byte();
+    assert_eq!(fibonacci.state(), 0xace1);
+    assert_eq!(galois.state(), 0xace1);
+}
+#[test]
+fn seek_matches_sequential_pass() {
+    let mut lfsr = GaloisLfsr::new(16, &TAPS_16, 0xace1);
+    let all = keystream(&mut lfsr, 100);
+    lfsr.seek_to(37);
+    assert_eq!(keystream(&mut lfsr, 20), &all[37..57]);
+    lfsr.seek_to(90);
+    assert_eq!(keystream(&mut lfsr, 10), &all[90..]);
+    assert_eq!(lfsr.position(), 100);
+
+    let all = keystream(&mut FibonacciLfsr::new(16, &TAPS_16, 0xace1), 100);
+    let mut lfsr = FibonacciLfsr::new(16, &TAPS_16, 0xace1);
+    lfsr.seek_to(60);
+    assert_eq!(keystream(&mut lfsr, 40), &all[60..]);
+    lfsr.seek_to(3);
+    assert_eq!(keystream(&mut lfsr, 5), &all[3..8]);
+}
+#[test]
+fn munge_roundtrip() {
+    let input = b"scrambled for the wire";
+    let mut lfsr = FibonacciLfsr::new(16, &TAPS_16, 0xace1);
+    let munged: Vec<u8> = lfsr.munge(input).collect();
+    assert_ne!(munged, input);
+    let mut lfsr = FibonacciLfsr::new(16, &TAPS_16, 0xace1);
+    let unmunged: Vec<u8> = lfsr.munge(&munged).collect();
+    assert_eq!(unmunged, input);
+}
+#[test]
+fn rejects_invalid_registers() {
+    assert_eq!(
+        FibonacciLfsr::try_new(0, &[1], 1).unwrap_err(),
+        LfsrError::InvalidWidth(0)
+    );
+    assert_eq!(
+        GaloisLfsr::try_new(65, &[65], 1).unwrap_err(),
+        LfsrError::InvalidWidth(65)
+    );
+    assert_eq!(
+        FibonacciLfsr::try_new(16, &[], 1).unwrap_err(),
+        LfsrError::NoTaps
+    );
+    assert_eq!(
+        GaloisLfsr::try_new(16, &[16, 17], 1).unwrap_err(),
+        LfsrError::InvalidTap(17)
+    );
+    assert_eq!(
+        FibonacciLfsr::try_new(16, &[16, 0], 1).unwrap_err(),
+        LfsrError::InvalidTap(0)
+    );
+    assert_eq!(
+        GaloisLfsr::try_new(16, &TAPS_16, 0x1_0000).unwrap_err(),
+        LfsrError::SeedTooWide
+    );
+    assert_eq!(
+        FibonacciLfsr::try_new(16, &TAPS_16, 0).unwrap_err(),
+        LfsrError::ZeroSeed
+    );
+}
+#[test]
+#[should_panic(expected = "invalid LFSR: seed must not be zero")]
+fn new_panics_on_zero_seed() {
+    GaloisLfsr::new(16, &TAPS_16, 0);
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_and_reader_roundtrip() {
+    let input = b"scrambled for the wire, and unscrambled again";
+    let mut writer = GaloisLfsr::new(16, &TAPS_16, 0xace1).writer(Vec::new());
+    writer.write_all(input).unwrap();
+    let (munged, _) = writer.into_inner();
+
+    let mut reader = GaloisLfsr::new(16, &TAPS_16, 0xace1).reader(Cursor::new(munged));
+    reader.seek(SeekFrom::Start(10)).unwrap();
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, &input[10..]);
+}
@@ -0,0 +1,91 @@
+#![cfg(feature = "std")]
+
+use exercism::analysis::many_time_pad::{xor_pair, ManyTimePad, PlaceError};
+use exercism::analysis::ByteFrequencies;
+use exercism::xorcism::Xorcism;
+
+const MESSAGES: [&str; 3] = [
+    "Meet me at the old mill after dark.",
+    "The shipment arrives on the tenth of May",
+    "Burn this letter once you have read it",
+];
+
+/// every message munged from the start of the same long key
+fn pad() -> ManyTimePad {
+    let key: Vec<u8> = (0..64_u32).map(|i| (i * 151 + 89) as u8).collect();
+    ManyTimePad::new(
+        MESSAGES
+            .iter()
+            .map(|message| Xorcism::new(&key).munge(message.as_bytes()).collect())
+            .collect(),
+    )
+}
+
+#[test]
+fn xor_pair_cancels_the_keystream() {
+    let pad = pad();
+    let xored = xor_pair(&pad.ciphertexts()[0], &pad.ciphertexts()[1]);
+    assert_eq!(xored.len(), MESSAGES[0].len());
+    assert_eq!(
+        xored,
+        xor_pair(MESSAGES[0].as_bytes(), MESSAGES[1].as_bytes())
+    );
+}
+#[test]
+fn drag_reveals_the_other_message() {
+    let pad = pad();
+    let matches = pad.drag(b" the ", &ByteFrequencies::english());
+    // " the " is in message 0 at offset 10; message 1 has "nt ar" there
+    let found = matches
+        .iter()
+        .find(|found| found.pair == (0, 1) && found.offset == 10)
+        .unwrap();
+    assert_eq!(found.revealed, b"nt ar");
+    // " the " is also in message 1 at offset 23, where message 2 has "ou ha"
+    assert!(matches
+        .iter()
+        .any(|found| found.pair == (1, 2) && found.offset == 23 && found.revealed == b"ou ha"));
+    // only text is ever revealed, best first
+    assert!(matches.iter().all(|found| found
+        .revealed
+        .iter()
+        .all(|byte| byte.is_ascii() && !byte.is_ascii_control())));
+    assert!(matches
+        .windows(2)
+        .all(|pair| pair[0].score >= pair[1].score));
+}
+#[test]
+fn drag_empty_crib() {
+    assert!(pad().drag(b"", &ByteFrequencies::english()).is_empty());
+}
+#[test]
+fn placing_text_decrypts_every_message() {
+    let mut pad = pad();
+    pad.place(0, 10, b" the ").unwrap();
+    let plaintext = pad.plaintext(1).unwrap();
+    assert_eq!(plaintext.len(), MESSAGES[1].len());
+    assert!(plaintext[..10].iter().all(Option::is_none));
+    let revealed: Vec<u8> = plaintext[10..15].iter().map(|byte| byte.unwrap()).collect();
+    assert_eq!(revealed, b"nt ar");
+    assert_eq!(pad.keystream().iter().flatten().count(), 5);
+
+    pad.place(1, 0, MESSAGES[1].as_bytes()).unwrap();
+    for (message, expect) in MESSAGES.iter().enumerate() {
+        let plaintext: Option<Vec<u8>> = pad.plaintext(message).unwrap().into_iter().collect();
+        assert_eq!(plaintext.unwrap(), expect.as_bytes());
+    }
+
+    pad.forget(5, 100);
+    assert_eq!(pad.keystream().iter().flatten().count(), 5);
+    assert_eq!(pad.plaintext(3), None);
+}
+#[test]
+fn placing_text_out_of_bounds() {
+    let mut pad = pad();
+    assert_eq!(pad.place(3, 0, b"x"), Err(PlaceError::NoSuchMessage(3)));
+    assert_eq!(
+        pad.place(0, 30, b"dark. "),
+        Err(PlaceError::OutOfBounds { offset: 30, len: 6 })
+    );
+    assert!(pad.keystream().iter().all(Option::is_none));
+}
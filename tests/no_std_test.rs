@@ -0,0 +1,95 @@
+//! The core munger used the way firmware would: no `std`, no allocator, fixed buffers only.
+//!
+//! Builds and runs with `--no-default-features`.
+#![no_std]
+
+use exercism::checkpoint::{Checkpoint, CHECKPOINT_LEN};
+use exercism::keystream::{Keystream, SplitMix64};
+use exercism::xorcism::{Xorcism, STATE_LEN};
+
+const INPUT: &[u8; 53] = b"This is super-secret, cutting edge encryption, folks.";
+
+fn munged(key: &[u8]) -> [u8; 53] {
+    let mut out = *INPUT;
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte ^= key[i % key.len()];
+    }
+    out
+}
+
+#[test]
+fn munge_in_place_on_a_fixed_buffer() {
+    let mut buf = *INPUT;
+    Xorcism::new("abcde").munge_in_place(&mut buf);
+    assert_eq!(buf, munged(b"abcde"));
+}
+#[test]
+fn munge_into_leaves_source_untouched() {
+    let src = *INPUT;
+    let mut dst = [0; 53];
+    let mut xs = Xorcism::new("abcde");
+    xs.munge_into(&src, &mut dst);
+    assert_eq!(src, *INPUT);
+    assert_eq!(dst, munged(b"abcde"));
+    assert_eq!(xs.position(), 53);
+}
+#[test]
+fn munge_into_in_pieces() {
+    let mut dst = [0; 53];
+    let mut xs = Xorcism::new("abcde");
+    for (src, dst) in INPUT.chunks(7).zip(dst.chunks_mut(7)) {
+        xs.munge_into(src, dst);
+    }
+    assert_eq!(dst, munged(b"abcde"));
+}
+#[test]
+#[should_panic]
+fn munge_into_needs_equal_lengths() {
+    Xorcism::new("abcde").munge_into(INPUT, &mut [0; 52]);
+}
+#[test]
+fn save_and_restore_state() {
+    let mut buf = *INPUT;
+    let mut xs = Xorcism::new("abcde");
+    xs.munge_in_place(&mut buf[..17]);
+    let state: [u8; STATE_LEN] = xs.save_state();
+    assert_eq!(state, 17_u64.to_be_bytes());
+
+    // as if after a reset: a fresh munger picks up where the old one stopped
+    let mut xs = Xorcism::new("abcde");
+    xs.restore_state(&state);
+    assert_eq!(xs.position(), 17);
+    xs.munge_in_place(&mut buf[17..]);
+    assert_eq!(buf, munged(b"abcde"));
+}
+#[test]
+fn restored_state_rewinds() {
+    let mut xs = Xorcism::new("abcde");
+    let start = xs.save_state();
+    let mut first = *INPUT;
+    xs.munge_in_place(&mut first);
+    xs.restore_state(&start);
+    let mut second = *INPUT;
+    xs.munge_in_place(&mut second);
+    assert_eq!(first, second);
+}
+#[test]
+fn keystreams_munge_into() {
+    let mut expect = *INPUT;
+    SplitMix64::new(42).munge_in_place(&mut expect);
+    let mut dst = [0; 53];
+    SplitMix64::new(42).munge_into(INPUT, &mut dst);
+    assert_eq!(dst, expect);
+}
+#[test]
+fn checkpoint_in_a_fixed_buffer() {
+    let mut buf = *INPUT;
+    let mut xs = Xorcism::new("abcde");
+    xs.munge_in_place(&mut buf[..30]);
+    let saved: [u8; CHECKPOINT_LEN] = xs.checkpoint().to_bytes();
+
+    let (checkpoint, _) = Checkpoint::from_bytes(&saved).unwrap();
+    let mut xs = Xorcism::resume("abcde", &checkpoint).unwrap();
+    xs.munge_in_place(&mut buf[30..]);
+    assert_eq!(buf, munged(b"abcde"));
+}
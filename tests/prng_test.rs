@@ -0,0 +1,130 @@
+use exercism::keystream::prng::{PrngError, SplitMix64, Xorshift64};
+use exercism::keystream::Keystream;
+#[cfg(feature = "io")]
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+fn keystream<Stream: Keystream>(stream: &mut Stream, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    stream.fill(&mut buf);
+    buf
+}
+
+fn le_bytes(words: &[u64]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+#[test]
+fn splitmix64_vector() {
+    let mut splitmix = SplitMix64::new(1234567);
+    assert_eq!(
+        keystream(&mut splitmix, 40),
+        le_bytes(&[
+            6457827717110365317,
+            3203168211198807973,
+            9817491932198370423,
+            4593380528125082431,
+            16408922859458223821,
+        ])
+    );
+}
+#[test]
+fn splitmix64_zero_seed() {
+    let mut splitmix = SplitMix64::new(0);
+    assert_eq!(
+        keystream(&mut splitmix, 24),
+        le_bytes(&[
+            16294208416658607535,
+            7960286522194355700,
+            487617019471545679
+        ])
+    );
+}
+#[test]
+fn xorshift64_vector() {
+    let mut xorshift = Xorshift64::new(88172645463325252);
+    assert_eq!(
+        keystream(&mut xorshift, 32),
+        le_bytes(&[
+            8748534153485358512,
+            3040900993826735515,
+            3453997556048239312,
+            16431732851926010853,
+        ])
+    );
+}
+#[test]
+fn xorshift64_seed_one_vector() {
+    let mut xorshift = Xorshift64::new(1);
+    assert_eq!(
+        keystream(&mut xorshift, 32),
+        le_bytes(&[
+            1082269761,
+            1152992998833853505,
+            11177516664432764457,
+            17678023832001937445,
+        ])
+    );
+}
+#[test]
+fn xorshift64_rejects_zero_seed() {
+    assert_eq!(Xorshift64::try_new(0).unwrap_err(), PrngError::ZeroSeed);
+}
+#[test]
+#[should_panic(expected = "invalid xorshift seed: seed must not be zero")]
+fn xorshift64_new_panics_on_zero_seed() {
+    Xorshift64::new(0);
+}
+#[test]
+fn seek_matches_sequential_pass() {
+    let all = keystream(&mut SplitMix64::new(42), 100);
+    let mut splitmix = SplitMix64::new(42);
+    for &(start, end) in &[(13, 40), (0, 8), (64, 100), (5, 6), (8, 9)] {
+        splitmix.seek_to(start);
+        assert_eq!(
+            keystream(&mut splitmix, (end - start) as usize),
+            &all[start as usize..end as usize]
+        );
+        assert_eq!(splitmix.position(), end);
+    }
+
+    let all = keystream(&mut Xorshift64::new(42), 100);
+    let mut xorshift = Xorshift64::new(42);
+    for &(start, end) in &[
+        (13, 40),
+        (0, 8),
+        (64, 100),
+        (5, 6),
+        (8, 9),
+        (12, 16),
+        (8, 20),
+    ] {
+        xorshift.seek_to(start);
+        assert_eq!(
+            keystream(&mut xorshift, (end - start) as usize),
+            &all[start as usize..end as usize]
+        );
+        assert_eq!(xorshift.position(), end);
+    }
+}
+#[test]
+fn munge_roundtrip() {
+    let input = b"scrambled by a counter";
+    let munged: Vec<u8> = SplitMix64::new(7).munge(input).collect();
+    assert_ne!(munged, input);
+    let unmunged: Vec<u8> = SplitMix64::new(7).munge(&munged).collect();
+    assert_eq!(unmunged, input);
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_and_reader_roundtrip() {
+    let input = b"scrambled by a counter, and unscrambled again";
+    let mut writer = Xorshift64::new(7).writer(Vec::new());
+    writer.write_all(input).unwrap();
+    let (munged, _) = writer.into_inner();
+
+    let mut reader = Xorshift64::new(7).reader(Cursor::new(munged));
+    reader.seek(SeekFrom::Start(11)).unwrap();
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, &input[11..]);
+}
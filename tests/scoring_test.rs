@@ -0,0 +1,120 @@
+#![cfg(feature = "std")]
+
+use exercism::analysis::scoring::{ChiSquared, PrintableAscii, Utf8};
+use exercism::analysis::{break_repeating_key, recover_key, ByteFrequencies, PlaintextScorer};
+use exercism::xorcism::Xorcism;
+
+const ENGLISH: &[u8] = include_bytes!("data/english.txt");
+const LOG: &[u8] = b"2024-03-01T12:00:01Z INFO  request id=7f3a path=/api/v1/users status=200 ms=12
+2024-03-01T12:00:02Z WARN  request id=7f3b path=/api/v1/login status=401 ms=3
+2024-03-01T12:00:02Z INFO  request id=7f3c path=/api/v1/users/42 status=200 ms=8
+2024-03-01T12:00:05Z ERROR request id=7f3d path=/api/v1/export status=500 ms=30012
+2024-03-01T12:00:07Z INFO  request id=7f3e path=/healthz status=200 ms=1
+2024-03-01T12:00:09Z INFO  request id=7f3f path=/api/v1/users status=200 ms=11
+2024-03-01T12:00:12Z WARN  request id=7f40 path=/api/v1/login status=401 ms=4
+2024-03-01T12:00:15Z INFO  request id=7f41 path=/api/v1/orders status=200 ms=25
+";
+
+fn key(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 73 + 41) as u8 ^ 0x5a).collect()
+}
+
+#[test]
+fn printable_ascii_ratio() {
+    assert_eq!(PrintableAscii.score(b"plain text\n"), 1.0);
+    assert_eq!(PrintableAscii.score(b"ab\x00\xff"), 0.5);
+    assert_eq!(PrintableAscii.score(b""), 1.0);
+}
+#[test]
+fn utf8_ratio() {
+    assert_eq!(Utf8.score("naïve café ☕".as_bytes()), 1.0);
+    assert_eq!(Utf8.score(b"ab\xffcd"), 0.8);
+    // a truncated sequence at the end is invalid
+    assert_eq!(Utf8.score(b"abc\xe2\x98"), 0.6);
+    assert_eq!(Utf8.score(b""), 1.0);
+}
+#[test]
+fn chi_squared_prefers_the_expected_distribution() {
+    let chi = ChiSquared::new(ByteFrequencies::english());
+    assert!(chi.statistic(b"the quick brown fox jumps over the lazy dog") >= 0.0);
+    // all the right letters in the wrong proportions
+    assert!(
+        chi.score(b"eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee")
+            < chi.score(b"the quick brown fox jumps over the lazy dog")
+    );
+    assert!(chi.score(b"zqxj zqxj zqxj zqxj") < chi.score(b"then that this them"));
+}
+#[test]
+fn chi_squared_is_finite() {
+    assert_eq!(
+        ChiSquared::new(ByteFrequencies::english()).statistic(b""),
+        0.0
+    );
+
+    let mut weights = [0.0; 256];
+    weights[b'a' as usize] = 1.0;
+    let chi = ChiSquared::new(ByteFrequencies::from_weights(weights));
+    assert_eq!(chi.statistic(b"aaaa"), 0.0);
+    assert!(chi.statistic(b"abcd").is_finite());
+    assert!(ChiSquared::new(ByteFrequencies::from_weights([0.0; 256]))
+        .statistic(b"abcd")
+        .is_finite());
+}
+#[test]
+fn byte_frequencies_score_is_log_likelihood() {
+    let english = ByteFrequencies::english();
+    assert_eq!(
+        english.score(b"hello"),
+        english.log_likelihood(b"hello".iter().copied())
+    );
+}
+#[test]
+fn corpus_file_model() {
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("scoring_corpus.log");
+    std::fs::write(&path, LOG).unwrap();
+    let model = ByteFrequencies::from_corpus_file(&path).unwrap();
+    assert_eq!(model, ByteFrequencies::from_sample(LOG));
+    assert!(model.probability(b'=') > model.probability(b'q'));
+    assert!(ByteFrequencies::from_corpus_file(path.with_extension("missing")).is_err());
+}
+#[test]
+fn scorers_drive_key_recovery() {
+    let scorers: Vec<Box<dyn PlaintextScorer>> = vec![
+        Box::new(ByteFrequencies::english()),
+        Box::new(ChiSquared::new(ByteFrequencies::english())),
+    ];
+    for scorer in &scorers {
+        for len in [1, 5, 16] {
+            let key = key(len);
+            let ciphertext: Vec<u8> = Xorcism::new(&key).munge(ENGLISH).collect();
+            let recovered = recover_key(&ciphertext, len, scorer).unwrap();
+            assert_eq!(recovered.key, key);
+        }
+    }
+}
+#[test]
+fn trained_model_breaks_logs() {
+    // train on logs, then break a different log
+    let (training, text) = LOG.split_at(LOG.len() / 2);
+    let text = text.repeat(4);
+    let model = ByteFrequencies::from_sample(training);
+    let key = key(6);
+    let ciphertext: Vec<u8> = Xorcism::new(&key).munge(&text).collect();
+    let recovered = break_repeating_key(&ciphertext, 20, &model).unwrap();
+    assert_eq!(recovered.key, key);
+    assert_eq!(recovered.plaintext, text);
+}
+#[test]
+fn utf8_narrows_down_single_byte_key() {
+    let text = "Ça va très bien, merci — et toi? Ελληνικά και 日本語 также.".repeat(3);
+    let ciphertext: Vec<u8> = Xorcism::new(&[0xa7]).munge(text.as_bytes()).collect();
+    let valid: Vec<u8> = (0..=u8::MAX)
+        .filter(|&key| {
+            let candidate: Vec<u8> = Xorcism::new(&[key]).munge(&ciphertext).collect();
+            Utf8.score(&candidate) == 1.0
+        })
+        .collect();
+    assert!(valid.contains(&0xa7));
+    // only flipping low bits keeps the multibyte sequences intact
+    assert!(valid.len() <= 16, "{:?}", valid);
+}
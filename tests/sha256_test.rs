@@ -0,0 +1,74 @@
+use exercism::sha256::{hmac_sha256, sha256, HmacSha256, Sha256};
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn empty_message() {
+    assert_eq!(
+        hex(&sha256(b"")),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
+#[test]
+fn one_block_message() {
+    assert_eq!(
+        hex(&sha256(b"abc")),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+#[test]
+fn two_block_message() {
+    assert_eq!(
+        hex(&sha256(
+            b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+        )),
+        "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+    );
+}
+#[test]
+fn million_a() {
+    let mut hash = Sha256::new();
+    for _ in 0..1000 {
+        hash.update(&[b'a'; 1000]);
+    }
+    assert_eq!(
+        hex(&hash.finalize()),
+        "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+    );
+}
+#[test]
+fn incremental_matches_one_shot() {
+    let data: Vec<u8> = (0..300).map(|n| (n * 7) as u8).collect();
+    for split in [0, 1, 55, 56, 63, 64, 65, 128, 299, 300] {
+        let mut hash = Sha256::new();
+        hash.update(&data[..split]);
+        hash.update(&data[split..]);
+        assert_eq!(hash.finalize(), sha256(&data), "split at {}", split);
+    }
+}
+#[test]
+fn hmac_rfc4231_case_1() {
+    assert_eq!(
+        hex(&hmac_sha256(&[0x0b; 20], b"Hi There")),
+        "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+    );
+}
+#[test]
+fn hmac_rfc4231_case_2() {
+    assert_eq!(
+        hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+#[test]
+fn hmac_rfc4231_long_key() {
+    let mut hmac = HmacSha256::new(&[0xaa; 131]);
+    hmac.update(b"Test Using Larger Than Block-Size Key");
+    hmac.update(b" - Hash Key First");
+    assert_eq!(
+        hex(&hmac.finalize()),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+}
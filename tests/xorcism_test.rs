@@ -1,5 +1,40 @@
-use exercism::xorcism::Xorcism;
-use std::io::{Read, Write};
+use exercism::xorcism::{Xorcism, XorcismError};
+use std::borrow::Cow;
+#[cfg(feature = "io")]
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+
+/// a reader which hands out at most `chunk` bytes per call, like a pipe or socket would
+#[cfg(feature = "io")]
+struct ShortReader<R> {
+    inner: R,
+    chunk: usize,
+}
+#[cfg(feature = "io")]
+impl<R: Read> Read for ShortReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+/// a writer which accepts at most `chunk` bytes per call, like a pipe or socket would
+#[cfg(feature = "io")]
+struct ShortWriter<W> {
+    inner: W,
+    chunk: usize,
+}
+#[cfg(feature = "io")]
+impl<W: Write> Write for ShortWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.inner.write(&buf[..len])
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
 
 #[test]
 fn munge_in_place_identity() {
@@ -61,6 +96,322 @@ fn statefulness() {
     assert_ne!(out4, out5);
     assert_eq!(out1, out5);
 }
+#[test]
+fn munge_is_lazy() {
+    let mut xs = Xorcism::new(&[1, 2, 3]);
+    let output: Vec<u8> = xs.munge(std::iter::repeat(0_u8)).take(7).collect();
+    assert_eq!(output, &[1, 2, 3, 1, 2, 3, 1]);
+    // only the bytes taken advanced the key
+    let output: Vec<u8> = xs.munge([0, 0]).collect();
+    assert_eq!(output, &[2, 3]);
+}
+#[test]
+fn munge_exact_size() {
+    let mut xs = Xorcism::new(&[1, 2, 3]);
+    let mut munged = xs.munge(&[0; 5]);
+    assert_eq!(munged.len(), 5);
+    munged.next();
+    assert_eq!(munged.len(), 4);
+}
+#[test]
+fn munge_rev_matches_forward() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut forward = Xorcism::new(key);
+    let mut backward = forward.clone();
+    let mut expect: Vec<u8> = forward.munge(input).collect();
+    expect.reverse();
+    let output: Vec<u8> = backward.munge(input).rev().collect();
+    assert_eq!(output, expect);
+    // the key has advanced the same distance either way
+    let after_forward: Vec<u8> = forward.munge(input).collect();
+    let after_backward: Vec<u8> = backward.munge(input).collect();
+    assert_eq!(after_forward, after_backward);
+}
+#[test]
+fn munge_from_both_ends() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut xs = Xorcism::new(key);
+    let expect: Vec<u8> = xs.clone().munge(input).collect();
+    let mut munged = xs.munge(input);
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some(byte) = munged.next() {
+        front.push(byte);
+        if let Some(byte) = munged.next_back() {
+            back.push(byte);
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, expect);
+}
+#[test]
+fn seek_to_matches_sequential_pass() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+    for start in 0..input.len() {
+        let mut xs = Xorcism::new(key);
+        xs.seek_to(start as u64);
+        assert_eq!(xs.position(), start as u64);
+        let output: Vec<u8> = xs.munge(&input[start..]).collect();
+        assert_eq!(output, &expect[start..], "start {}", start);
+        assert_eq!(xs.position(), input.len() as u64);
+    }
+}
+#[test]
+fn position_tracks_munged_bytes() {
+    let mut xs = Xorcism::new(&[1, 2, 3]);
+    assert_eq!(xs.position(), 0);
+    xs.munge_in_place(&mut [0; 5]);
+    assert_eq!(xs.position(), 5);
+    xs.munge(&[0; 4]).rev().for_each(drop);
+    assert_eq!(xs.position(), 9);
+    xs.seek_to(u64::MAX - 1);
+    assert_eq!(xs.position(), u64::MAX - 1);
+    let output: Vec<u8> = xs.munge(&[0]).collect();
+    assert_eq!(output, &[[1, 2, 3][((u64::MAX - 1) % 3) as usize]]);
+}
+#[test]
+#[cfg(feature = "std")]
+fn parallel_matches_sequential() {
+    // long enough to be split between several threads, and not a multiple of anything
+    let input: Vec<u8> = (0..300_007_u32).map(|n| (n * 7 + n / 251) as u8).collect();
+    for key_len in (1..64).chain([255, 256, 1000]) {
+        let key: Vec<u8> = (0..key_len).map(|i| (i * 31 + 17) as u8).collect();
+        for threads in [0, 1, 2, 3, 4, 7] {
+            for start in [0, 5, key_len as u64 + 3] {
+                let mut sequential = Xorcism::new(&key);
+                sequential.seek_to(start);
+                let mut expect = input.clone();
+                sequential.munge_in_place(&mut expect);
+
+                let mut parallel = Xorcism::new(&key);
+                parallel.seek_to(start);
+                let mut output = input.clone();
+                parallel.munge_in_place_parallel(&mut output, threads);
+
+                assert!(
+                    output == expect,
+                    "key_len {} threads {} start {}",
+                    key_len,
+                    threads,
+                    start
+                );
+                assert_eq!(parallel.position(), sequential.position());
+                let next: Vec<u8> = parallel.munge(&[0; 8]).collect();
+                let expect_next: Vec<u8> = sequential.munge(&[0; 8]).collect();
+                assert_eq!(next, expect_next);
+            }
+        }
+    }
+}
+#[test]
+#[cfg(feature = "std")]
+fn parallel_small_buffers() {
+    let mut xs = Xorcism::new("abcde");
+    for len in [0, 1, 7, 100] {
+        let mut expect = vec![0; len];
+        let mut sequential = xs.clone();
+        sequential.munge_in_place(&mut expect);
+        let mut output = vec![0; len];
+        xs.munge_in_place_parallel(&mut output, 8);
+        assert_eq!(output, expect);
+        assert_eq!(xs.position(), sequential.position());
+    }
+}
+#[test]
+fn owned_keys_munge_like_borrowed_keys() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let expect: Vec<u8> = Xorcism::new(key).munge(input).collect();
+
+    let mut owned = Xorcism::with_key(key.as_bytes().to_vec());
+    assert_eq!(owned.munge(input).collect::<Vec<_>>(), expect);
+    let mut shared = Xorcism::with_key(Arc::<[u8]>::from(key.as_bytes()));
+    assert_eq!(shared.munge(input).collect::<Vec<_>>(), expect);
+    let mut cow = Xorcism::with_key(Cow::Borrowed(key.as_bytes()));
+    assert_eq!(cow.munge(input).collect::<Vec<_>>(), expect);
+}
+#[test]
+fn owned_key_is_send_sync_static() {
+    fn assert_send_sync_static<T: Send + Sync + 'static>(_: &T) {}
+    fn load_key() -> Xorcism<Vec<u8>> {
+        Xorcism::with_key(String::from("abcde").into_bytes())
+    }
+
+    let mut xs = load_key();
+    assert_send_sync_static(&xs);
+    let mut other = xs.clone();
+    let output = std::thread::spawn(move || {
+        let mut data = b"123455".to_vec();
+        other.munge_in_place(&mut data);
+        data
+    })
+    .join()
+    .unwrap();
+    assert_eq!(output, &[80, 80, 80, 80, 80, 84]);
+    assert_eq!(xs.key(), b"abcde");
+    assert_eq!(xs.munge(b"123455").collect::<Vec<_>>(), output);
+}
+#[test]
+fn try_with_key_rejects_empty_key() {
+    assert_eq!(
+        Xorcism::try_with_key(Vec::new()).err(),
+        Some(XorcismError::EmptyKey)
+    );
+}
+#[test]
+fn munge_in_place_matches_bytewise_munge() {
+    // cover both sides of the short key threshold and the pattern boundary, with data lengths
+    // which do and don't fill whole words
+    let data: Vec<u8> = (0..1500).map(|i| (i * 7 + 3) as u8).collect();
+    for key_len in (1..=130).chain([255, 256, 257, 600]) {
+        let key: Vec<u8> = (0..key_len).map(|i| (i * 31 + 17) as u8).collect();
+        for start in [0, 1, key_len / 2, key_len - 1, 1000] {
+            for len in [0, 1, 7, 8, 9, 63, 64, 65, 511, 512, 513, 1500 - start] {
+                let data = &data[start..start + len.min(1500 - start)];
+                let mut fast = Xorcism::new(&key);
+                fast.seek_to(start as u64);
+                let mut bytewise = fast.clone();
+
+                let mut output = data.to_vec();
+                fast.munge_in_place(&mut output);
+                let expect: Vec<u8> = bytewise.munge(data).collect();
+                assert_eq!(
+                    output, expect,
+                    "key {} start {} len {}",
+                    key_len, start, len
+                );
+                assert_eq!(fast.position(), bytewise.position());
+                // the key ends up in the same place, too
+                assert_eq!(
+                    fast.munge(&[0; 3]).collect::<Vec<_>>(),
+                    bytewise.munge(&[0; 3]).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+}
+#[test]
+fn try_new_rejects_empty_key() {
+    assert_eq!(Xorcism::try_new("").err(), Some(XorcismError::EmptyKey));
+    assert_eq!(
+        Xorcism::try_new(&[] as &[u8]).err(),
+        Some(XorcismError::EmptyKey)
+    );
+}
+#[test]
+fn try_new_accepts_key() {
+    let mut xs = Xorcism::try_new(&[1, 2, 3]).unwrap();
+    let output: Vec<u8> = xs.munge(&[1, 2, 3, 1]).collect();
+    assert_eq!(output, &[0, 0, 0, 0]);
+}
+#[test]
+#[should_panic(expected = "key must not be empty")]
+fn new_panics_on_empty_key() {
+    Xorcism::new("");
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_flushes_inner_writer() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+
+    let mut writer = Xorcism::new(key).writer(std::io::BufWriter::new(Vec::new()));
+    writer.write_all(input).unwrap();
+    assert!(writer.get_ref().get_ref().is_empty());
+    writer.flush().unwrap();
+    assert_eq!(writer.get_ref().get_ref(), &expect);
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_into_inner_keeps_key_position() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+
+    let (head, tail) = input.split_at(7);
+    let mut writer = Xorcism::new(key).writer(Vec::new());
+    writer.write_all(head).unwrap();
+    let (mut dest, mut xs) = writer.into_inner();
+    dest.extend(xs.munge(tail));
+    assert_eq!(dest, expect);
+}
+#[cfg(feature = "io")]
+#[test]
+fn reader_into_inner_keeps_key_position() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+
+    let mut reader = Xorcism::new(key).reader(input);
+    let mut head = [0; 7];
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(reader.get_ref().len(), input.len() - head.len());
+    let (rest, mut xs) = reader.into_inner();
+    let mut output = head.to_vec();
+    output.extend(xs.munge(rest));
+    assert_eq!(output, expect);
+}
+#[cfg(feature = "io")]
+#[test]
+fn reader_get_mut_reaches_inner_reader() {
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut reader = Xorcism::new(&[0]).reader(input);
+    *reader.get_mut() = &input[5..];
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, &input[5..]);
+}
+#[cfg(feature = "io")]
+#[test]
+fn reader_seeks_with_inner_reader() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+
+    let mut reader = Xorcism::new(key).reader(Cursor::new(input));
+    for (seek, start) in [
+        (SeekFrom::Start(11), 11),
+        (SeekFrom::Current(-9), 6),
+        (SeekFrom::End(-7), input.len() - 7),
+        (SeekFrom::Start(0), 0),
+    ] {
+        assert_eq!(reader.seek(seek).unwrap(), start as u64);
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, expect[start..start + 4]);
+    }
+}
+#[cfg(feature = "io")]
+#[test]
+fn writer_seeks_with_inner_writer() {
+    let key = "abcde";
+    let input = "This is super-secret, cutting edge encryption, folks.".as_bytes();
+    let mut expect = input.to_owned();
+    Xorcism::new(key).munge_in_place(&mut expect);
+
+    let mut writer = Xorcism::new(key).writer(Cursor::new(Vec::new()));
+    // write the input back to front in uneven chunks
+    let mut end = input.len();
+    while end > 0 {
+        let start = end.saturating_sub(6);
+        writer.seek(SeekFrom::Start(start as u64)).unwrap();
+        writer.write_all(&input[start..end]).unwrap();
+        end = start;
+    }
+    let (dest, _) = writer.into_inner();
+    assert_eq!(dest.into_inner(), expect);
+}
 macro_rules! test_cases {
     ($($name:ident, $key:literal, $input:literal, $expect:expr);+) => {
         $(mod $name {
@@ -206,6 +557,41 @@ macro_rules! test_cases {
                     assert_eq!(buf, INPUT.as_bytes());
                 }
                 #[test]
+                fn reader_short_reads() {
+                    for chunk in 1..=INPUT.len() {
+                        let short = ShortReader { inner: INPUT.as_bytes(), chunk };
+                        let mut reader = Xorcism::new(KEY).reader(short);
+                        let mut buf = Vec::with_capacity(INPUT.len());
+                        let bytes_read = reader.read_to_end(&mut buf).unwrap();
+                        assert_eq!(bytes_read, INPUT.len());
+                        assert_eq!(buf, EXPECT, "chunk size {}", chunk);
+                    }
+                }
+                #[test]
+                fn reader_short_reads_roundtrip() {
+                    let xs = Xorcism::new(KEY);
+                    for chunk in 1..=INPUT.len() {
+                        let short = ShortReader { inner: INPUT.as_bytes(), chunk };
+                        let reader1 = xs.clone().reader(short);
+                        let short = ShortReader { inner: reader1, chunk: chunk + 1 };
+                        let mut reader2 = xs.clone().reader(short);
+                        let mut buf = Vec::with_capacity(INPUT.len());
+                        let bytes_read = reader2.read_to_end(&mut buf).unwrap();
+                        assert_eq!(bytes_read, INPUT.len());
+                        assert_eq!(buf, INPUT.as_bytes(), "chunk size {}", chunk);
+                    }
+                }
+                #[test]
+                fn reader_take() {
+                    let split = INPUT.len() / 2;
+                    let mut reader = Xorcism::new(KEY).reader(INPUT.as_bytes().take(split as u64));
+                    // read into an oversized buffer: only the bytes actually read may advance the key
+                    let mut buf = vec![0; INPUT.len() * 2];
+                    let bytes_read = reader.read(&mut buf).unwrap();
+                    assert_eq!(bytes_read, split);
+                    assert_eq!(&buf[..bytes_read], &EXPECT[..split]);
+                }
+                #[test]
                 fn writer_munges() {
                     let mut writer_dest = Vec::new();
                     {
@@ -225,6 +611,33 @@ macro_rules! test_cases {
                     }
                     assert_eq!(writer_dest, INPUT.as_bytes());
                 }
+                #[test]
+                fn writer_short_writes() {
+                    for chunk in 1..=INPUT.len() {
+                        let mut writer_dest = Vec::new();
+                        {
+                            let short = ShortWriter { inner: &mut writer_dest, chunk };
+                            let mut writer = Xorcism::new(KEY).writer(short);
+                            assert!(writer.write_all(INPUT.as_bytes()).is_ok());
+                        }
+                        assert_eq!(writer_dest, EXPECT, "chunk size {}", chunk);
+                    }
+                }
+                #[test]
+                fn writer_short_writes_roundtrip() {
+                    let xs = Xorcism::new(KEY);
+                    for chunk in 1..=INPUT.len() {
+                        let mut writer_dest = Vec::new();
+                        {
+                            let short = ShortWriter { inner: &mut writer_dest, chunk };
+                            let writer1 = xs.clone().writer(short);
+                            let short = ShortWriter { inner: writer1, chunk: chunk + 1 };
+                            let mut writer2 = xs.clone().writer(short);
+                            assert!(writer2.write_all(INPUT.as_bytes()).is_ok());
+                        }
+                        assert_eq!(writer_dest, INPUT.as_bytes(), "chunk size {}", chunk);
+                    }
+                }
             }
         })+
     };